@@ -1,69 +1,227 @@
+use crate::score::Score;
 use chess::{BitBoard, Color, Piece, Square};
 
+/// Material value of a piece, with a middlegame and an endgame value
 pub struct PieceValuePair {
     pub(crate) piece: Piece,
-    pub(crate) value: i32,
-    pub(crate) forward_scale: i32,
-    pub(crate) center_scale: i32,
+    pub(crate) value: Score,
     pub(crate) attack_weight: i32,
 }
 
 pub const PIECE_VALUES: [PieceValuePair; 6] = [
     PieceValuePair {
         piece: Piece::Pawn,
-        value: 100,
-        forward_scale: 5,
-        center_scale: 6,
+        value: Score::new(100, 120),
         attack_weight: 2,
     },
     PieceValuePair {
         piece: Piece::Knight,
-        value: 300,
-        forward_scale: 2,
-        center_scale: 1,
+        value: Score::new(300, 290),
         attack_weight: 2,
     },
     PieceValuePair {
         piece: Piece::Bishop,
-        value: 300,
-        forward_scale: 2,
-        center_scale: -2,
+        value: Score::new(300, 310),
         attack_weight: 2,
     },
     PieceValuePair {
         piece: Piece::Rook,
-        value: 500,
-        forward_scale: 4,
-        center_scale: 0,
+        value: Score::new(500, 530),
         attack_weight: 3,
     },
     PieceValuePair {
         piece: Piece::Queen,
-        value: 700,
-        forward_scale: -4,
-        center_scale: 0,
+        value: Score::new(700, 740),
         attack_weight: 5,
     },
     PieceValuePair {
         piece: Piece::King,
-        value: 0,
-        forward_scale: -4,
-        center_scale: -4,
+        value: Score::new(0, 0),
         attack_weight: 0,
     },
 ];
-pub const RANK_BITBOARDS: [BitBoard; 8] = [
-    BitBoard(0xFF),
-    BitBoard(0xFF << 8),
-    BitBoard(0xFF << 16),
-    BitBoard(0xFF << 24),
-    BitBoard(0xFF << 32),
-    BitBoard(0xFF << 40),
-    BitBoard(0xFF << 48),
-    BitBoard(0xFF << 56),
+/// Placement bonuses by piece in the middlegame, laid out as seen from White's side of the board:
+/// the first row is the eighth rank and the last row the first rank. Black uses the same tables
+/// mirrored vertically.
+#[rustfmt::skip]
+pub const MG_PIECE_SQUARE_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         10,  10,  20,  30,  30,  20,  10,  10,
+          5,   5,  10,  25,  25,  10,   5,   5,
+          0,   0,   0,  20,  20,   0,   0,   0,
+          5,  -5, -10,   0,   0, -10,  -5,   5,
+          5,  10,  10, -20, -20,  10,  10,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    // Knight
+    [
+        -50, -40, -30, -30, -30, -30, -40, -50,
+        -40, -20,   0,   0,   0,   0, -20, -40,
+        -30,   0,  10,  15,  15,  10,   0, -30,
+        -30,   5,  15,  20,  20,  15,   5, -30,
+        -30,   0,  15,  20,  20,  15,   0, -30,
+        -30,   5,  10,  15,  15,  10,   5, -30,
+        -40, -20,   0,   5,   5,   0, -20, -40,
+        -50, -40, -30, -30, -30, -30, -40, -50,
+    ],
+    // Bishop
+    [
+        -20, -10, -10, -10, -10, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   5,   5,  10,  10,   5,   5, -10,
+        -10,   0,  10,  10,  10,  10,   0, -10,
+        -10,  10,  10,  10,  10,  10,  10, -10,
+        -10,   5,   0,   0,   0,   0,   5, -10,
+        -20, -10, -10, -10, -10, -10, -10, -20,
+    ],
+    // Rook
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+          5,  10,  10,  10,  10,  10,  10,   5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+          0,   0,   0,   5,   5,   0,   0,   0,
+    ],
+    // Queen
+    [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+         -5,   0,   5,   5,   5,   5,   0,  -5,
+          0,   0,   5,   5,   5,   5,   0,  -5,
+        -10,   5,   5,   5,   5,   5,   0, -10,
+        -10,   0,   5,   0,   0,   0,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ],
+    // King
+    [
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -20, -30, -30, -40, -40, -30, -30, -20,
+        -10, -20, -20, -20, -20, -20, -20, -10,
+         20,  20,   0,   0,   0,   0,  20,  20,
+         20,  30,  10,   0,   0,  10,  30,  20,
+    ],
+];
+/// Placement bonuses by piece in the endgame, laid out like `MG_PIECE_SQUARE_TABLES`
+#[rustfmt::skip]
+pub const EG_PIECE_SQUARE_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         80,  80,  80,  80,  80,  80,  80,  80,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         30,  30,  30,  30,  30,  30,  30,  30,
+         15,  15,  15,  15,  15,  15,  15,  15,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    // Knight
+    [
+        -40, -30, -20, -20, -20, -20, -30, -40,
+        -30, -15,   0,   0,   0,   0, -15, -30,
+        -20,   0,  10,  15,  15,  10,   0, -20,
+        -20,   5,  15,  20,  20,  15,   5, -20,
+        -20,   0,  15,  20,  20,  15,   0, -20,
+        -20,   5,  10,  15,  15,  10,   5, -20,
+        -30, -15,   0,   5,   5,   0, -15, -30,
+        -40, -30, -20, -20, -20, -20, -30, -40,
+    ],
+    // Bishop
+    [
+        -15, -10, -10, -10, -10, -10, -10, -15,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -15, -10, -10, -10, -10, -10, -10, -15,
+    ],
+    // Rook
+    [
+          5,   5,   5,   5,   5,   5,   5,   5,
+         10,  10,  10,  10,  10,  10,  10,  10,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    // Queen
+    [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -10,   5,  10,  10,  10,  10,   5, -10,
+         -5,   5,  10,  15,  15,  10,   5,  -5,
+         -5,   5,  10,  15,  15,  10,   5,  -5,
+        -10,   5,  10,  10,  10,  10,   5, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ],
+    // King
+    [
+        -50, -40, -30, -20, -20, -30, -40, -50,
+        -30, -20, -10,   0,   0, -10, -20, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -30,   0,   0,   0,   0, -30, -30,
+        -50, -30, -30, -30, -30, -30, -30, -50,
+    ],
 ];
+pub const LIGHT_SQUARES: BitBoard = BitBoard(0x55AA_55AA_55AA_55AA);
 pub const MAX_DEPTH_INCREASE: u16 = 3;
-pub const SIDE_SCALAR: i32 = 10;
+pub const QUIESCENCE_CHECK_PLIES: u16 = 1;
+pub const SEE_KING_VALUE: i32 = 20000;
+pub const MAX_PLY: usize = 2 * MAX_SEARCH_DEPTH as usize;
+pub const TT_MOVE_SCORE: i32 = 1 << 30;
+pub const GOOD_CAPTURE_SCORE: i32 = 1 << 28;
+pub const KILLER_SCORE: i32 = 1 << 27;
+pub const BAD_CAPTURE_SCORE: i32 = -(1 << 28);
+pub const HISTORY_MAX: i32 = 1 << 14;
+pub const NULL_MOVE_MIN_DEPTH: u16 = 2;
+pub const NULL_MOVE_REDUCTION: u16 = 2;
+pub const NULL_MOVE_REDUCTION_DIVISOR: u16 = 4;
+pub const NULL_MOVE_VERIFICATION_DEPTH: u16 = 6;
+pub const LMR_MIN_DEPTH: u16 = 3;
+pub const LMR_MIN_MOVES_SEARCHED: usize = 3;
+pub const LMR_DEEP_MOVES_SEARCHED: usize = 8;
+pub const LMR_REDUCTION: u16 = 1;
+pub const LATE_MOVE_PRUNING_DEPTH: u16 = 3;
+pub const LATE_MOVE_PRUNING_COUNTS: [usize; 4] = [0, 8, 12, 20];
+pub const FUTILITY_MARGINS: [i32; 4] = [0, 1500, 3000, 5000];
+pub const MAX_SEARCH_DEPTH: u16 = 64;
+/// Nodes a Lazy SMP thread searches between additions to the shared node count
+pub const NODE_COUNT_BATCH: u64 = 1024;
+pub const DEFAULT_MOVES_TO_GO: u64 = 30;
+pub const INCREMENT_USAGE_PERCENT: u64 = 75;
+pub const HARD_LIMIT_SCALE: u64 = 4;
+/// Share of the remaining clock a single move may ever use, however few moves are left to play
+pub const HARD_LIMIT_MAX_PERCENT: u64 = 50;
+/// Milliseconds always kept on the clock on top of the move overhead
+pub const TIME_SAFETY_MARGIN: u64 = 20;
+pub const MATERIAL_SCALE: i32 = 10;
+pub const DRAW_SCORE: i32 = 0;
+pub const FIFTY_MOVE_PLIES: u16 = 100;
+pub const MATE_SCORE: i32 = 1_000_000;
+pub const MATE_BOUND: i32 = MATE_SCORE - 2 * MAX_SEARCH_DEPTH as i32;
+pub const INFINITY: i32 = MATE_SCORE + 1;
+pub const ASPIRATION_WINDOW: i32 = 250;
+/// King zones by king square: the king's square, the squares around it and the rank beyond those
+/// in front of a white king
 pub const WHITE_KING_DANGER_SQUARE_MAP: [BitBoard; 64] = [
     BitBoard(0x0000000000030303),
     BitBoard(0x0000000000070707),
@@ -130,6 +288,7 @@ pub const WHITE_KING_DANGER_SQUARE_MAP: [BitBoard; 64] = [
     BitBoard(0xE0E0000000000000),
     BitBoard(0xC0C0000000000000),
 ];
+/// King zones by king square, extending towards the first rank in front of a black king
 pub const BLACK_KING_DANGER_SQUARE_MAP: [BitBoard; 64] = [
     BitBoard(0x0000000000000303),
     BitBoard(0x0000000000000707),
@@ -196,6 +355,8 @@ pub const BLACK_KING_DANGER_SQUARE_MAP: [BitBoard; 64] = [
     BitBoard(0xE0E0E00000000000),
     BitBoard(0xC0C0C00000000000),
 ];
+/// King danger in search units by attack units, which grows quickly once several pieces join the
+/// attack. Attack units beyond the end of the table use its last entry.
 pub const ATTACK_WEIGHT_MAP: [i32; 100] = [
     0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85, 89, 97,
     105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272, 283, 295, 307,
@@ -204,6 +365,84 @@ pub const ATTACK_WEIGHT_MAP: [i32; 100] = [
     500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
 ];
 pub const PLAYER_SCALAR_MAP: [i32; 2] = [1, -1];
+/// Contribution of each piece type to the game phase, which starts at `MAX_PHASE` with all the
+/// pieces on the board and falls to 0 in a pawn endgame
+pub const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+/// Pawn structure terms, in the same units as `PIECE_VALUES`. Tables are indexed by the pawn's
+/// rank counted from its own side of the board.
+pub const PASSED_PAWN_BONUS: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(10, 15),
+    Score::new(15, 25),
+    Score::new(30, 50),
+    Score::new(50, 90),
+    Score::new(80, 140),
+    Score::new(0, 0),
+];
+pub const CANDIDATE_PASSER_BONUS: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(2, 5),
+    Score::new(4, 8),
+    Score::new(8, 15),
+    Score::new(15, 30),
+    Score::new(25, 50),
+    Score::new(0, 0),
+    Score::new(0, 0),
+];
+pub const CONNECTED_PAWN_BONUS: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(3, 3),
+    Score::new(5, 5),
+    Score::new(8, 8),
+    Score::new(14, 14),
+    Score::new(25, 25),
+    Score::new(40, 40),
+    Score::new(0, 0),
+];
+/// Share of a passed pawn's bonus kept while a piece stands on the square in front of it
+pub const BLOCKADED_PASSER_PERCENT: i32 = 50;
+pub const ISOLATED_PAWN_PENALTY: Score = Score::new(-10, -15);
+pub const DOUBLED_PAWN_PENALTY: Score = Score::new(-10, -20);
+pub const BACKWARD_PAWN_PENALTY: Score = Score::new(-8, -10);
+pub const MOBILITY_WEIGHT: Score = Score::new(1, 2);
+pub const KING_DANGER_ENDGAME_PERCENT: i32 = 25;
+/// Share of the attack units kept by attacker count, so that a lone piece near the king is rarely
+/// a real threat
+pub const ATTACKER_COUNT_PERCENT: [i32; 6] = [0, 25, 60, 80, 90, 100];
+/// Attack units for each piece type that can give a check on a square the defender does not
+/// cover, by piece index
+pub const SAFE_CHECK_UNITS: [usize; 6] = [0, 6, 4, 7, 8, 0];
+/// King cover terms, in the same units as `PIECE_VALUES`, on the king's file and the files beside
+/// it. Shield pawns are indexed by their rank counted from the king's side of the board, and
+/// storming pawns by that same rank, with index 0 for a file without such a pawn.
+pub const PAWN_SHIELD_BONUS: [Score; 8] = [
+    Score::new(-8, 0),
+    Score::new(10, 0),
+    Score::new(6, 0),
+    Score::new(2, 0),
+    Score::new(0, 0),
+    Score::new(0, 0),
+    Score::new(0, 0),
+    Score::new(0, 0),
+];
+pub const PAWN_STORM_PENALTY: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(0, 0),
+    Score::new(-20, 0),
+    Score::new(-12, 0),
+    Score::new(-6, 0),
+    Score::new(-2, 0),
+    Score::new(0, 0),
+    Score::new(0, 0),
+];
+/// Share of a storming pawn's penalty kept while a shield pawn blocks it
+pub const BLOCKED_STORM_PERCENT: i32 = 50;
+/// Files near the king without a friendly pawn, or without any pawn, while the enemy has a rook or
+/// queen to use them
+pub const KING_SEMI_OPEN_FILE_PENALTY: Score = Score::new(-8, 0);
+pub const KING_OPEN_FILE_PENALTY: Score = Score::new(-15, 0);
 
 #[allow(unused)]
 #[inline]
@@ -1,88 +1,153 @@
-use crate::constants::{PIECE_VALUES, PLAYER_SCALAR_MAP, RANK_BITBOARDS};
-use chess::Color::{Black, White};
-use chess::File::H;
-use chess::Piece::{Pawn, Queen};
-use chess::{Board, Color, MoveGen, Piece, Rank, Square};
+use crate::constants::{
+    LIGHT_SQUARES, MATERIAL_SCALE, MAX_PHASE, MOBILITY_WEIGHT, PHASE_WEIGHTS, PIECE_VALUES,
+    PLAYER_SCALAR_MAP,
+};
+use crate::king::eval_king_safety;
+use crate::pawns::{eval_pawn_structure, PawnTable};
+use crate::pst::piece_square;
+use crate::score::Score;
+use chess::Piece::{Bishop, Knight, Pawn, Queen, Rook};
+use chess::{Board, ChessMove, Color, File, MoveGen, Piece, Square, ALL_COLORS, EMPTY};
 use std::cmp::min;
 
 #[inline]
-pub fn eval_piece(piece: Piece, position: Square, player: Color) -> i32 {
+pub fn eval_piece(piece: Piece, position: Square, player: Color) -> Score {
     PLAYER_SCALAR_MAP[player.to_index()] * PIECE_VALUES[piece.to_index()].value
         + eval_piece_position(piece, position, player)
 }
 
 #[inline]
-pub fn eval_piece_position(piece: Piece, position: Square, player: Color) -> i32 {
-    let rank = position.get_rank() as i32;
-    let file = position.get_file() as i32;
-    PIECE_VALUES[piece.to_index()].forward_scale
-        * ((player == White) as i32 * rank
-            - (player == Black) as i32 * (Rank::Eighth.to_index() as i32 - rank))
-        + PIECE_VALUES[piece.to_index()].center_scale * min(file, H.to_index() as i32 - file)
+pub fn eval_piece_position(piece: Piece, position: Square, player: Color) -> Score {
+    PLAYER_SCALAR_MAP[player.to_index()] * piece_square(piece, position, player)
 }
 
-#[allow(unused)]
-pub fn eval_all_pieces_positional(board: &Board) -> i32 {
-    let mut val: i32 = 0;
+/// Material and placement of every piece, from White's point of view. The search keeps this up
+/// to date with `assess_incremental` instead of recomputing it at every node.
+pub fn eval_all_pieces_positional(board: &Board) -> Score {
+    let mut val = Score::ZERO;
     for piece_val_pair in PIECE_VALUES {
         let piece_bits = board.pieces(piece_val_pair.piece);
-        let white_pieces = board.color_combined(White) & piece_bits;
-        let black_pieces = board.color_combined(Black) & piece_bits;
-        for (rank, rank_bits) in RANK_BITBOARDS.iter().enumerate() {
-            let num_pieces = (white_pieces & rank_bits).popcnt() as i32;
-            val += (piece_val_pair.value + piece_val_pair.forward_scale * rank as i32) * num_pieces;
-        }
-        for (rank, rank_bits) in RANK_BITBOARDS.iter().enumerate() {
-            let num_pieces = (black_pieces & rank_bits).popcnt() as i32;
-            val -= (piece_val_pair.value + piece_val_pair.forward_scale * (7 - rank) as i32)
-                * num_pieces;
+        for player in ALL_COLORS {
+            for square in board.color_combined(player) & piece_bits {
+                val += eval_piece(piece_val_pair.piece, square, player);
+            }
         }
     }
     val
 }
 
-fn eval_pawn_extra(pos: Square, board: &Board) -> i32 {
-    let mut bonus: i32 = 0;
-    if let Some(below) = pos.down() {
-        if let Some(west_defender) = below.left() {
-            if board
-                .piece_on(west_defender)
-                .is_some_and(|piece| piece == Pawn)
-            {
-                bonus += 5;
-            }
-        }
-        if let Some(east_defender) = below.right() {
-            if board
-                .piece_on(east_defender)
-                .is_some_and(|piece| piece == Pawn)
-            {
-                bonus += 5;
-            }
+pub fn eval_mobility(board: &Board) -> Score {
+    let mut mobility = 0;
+    for mov in MoveGen::new_legal(board) {
+        if let Some(mover) = board.piece_on(mov.get_source()) {
+            mobility += (mover != Queen) as i32;
         }
     }
-    bonus
+    MOBILITY_WEIGHT * mobility
 }
 
-pub fn eval_overall_pawn_bonus(board: &Board) -> i32 {
-    let mut overall: i32 = 0;
-    let white_pawns = board.pieces(Pawn) & board.color_combined(White);
-    let black_pawns = board.pieces(Pawn) & board.color_combined(Black);
-    for pawn_loc in white_pawns {
-        overall += eval_pawn_extra(pawn_loc, board);
+/// Whether neither side has enough material left to deliver mate: bare kings, a single minor
+/// piece, or only bishops that all stand on squares of the same colour
+pub fn is_insufficient_material(board: &Board) -> bool {
+    if (board.pieces(Pawn) | board.pieces(Rook) | board.pieces(Queen)) != EMPTY {
+        return false;
     }
-    for pawn_loc in black_pawns {
-        overall -= eval_pawn_extra(pawn_loc, board);
+    let minors = board.pieces(Knight) | board.pieces(Bishop);
+    if minors.popcnt() <= 1 {
+        return true;
     }
-    overall
+    let bishops = *board.pieces(Bishop);
+    minors == bishops && ((bishops & LIGHT_SQUARES) == EMPTY || (bishops & !LIGHT_SQUARES) == EMPTY)
 }
 
-pub fn eval_mobility(board: &Board) -> i32 {
-    let mut mobility = 0;
-    for mov in MoveGen::new_legal(board) {
-        if let Some(mover) = board.piece_on(mov.get_source()) {
-            mobility += (mover != Queen) as i32;
+/// How far the game is from the endgame, from `MAX_PHASE` with every piece on the board down to 0
+/// once only kings and pawns are left
+#[inline]
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = PIECE_VALUES
+        .iter()
+        .map(|piece_val_pair| {
+            PHASE_WEIGHTS[piece_val_pair.piece.to_index()]
+                * board.pieces(piece_val_pair.piece).popcnt() as i32
+        })
+        .sum();
+    min(phase, MAX_PHASE)
+}
+
+/// Blends the middlegame and endgame halves of a score by the game phase
+#[inline]
+pub fn taper(board: &Board, score: Score) -> i32 {
+    let phase = game_phase(board);
+    (score.mg * phase + score.eg * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+/// Positional terms that are not tracked incrementally, from the side to move's point of view
+pub fn lazy_assess_board(board: &Board, pawn_table: &mut PawnTable) -> Score {
+    let mut val = eval_mobility(board);
+    if let Some(flipped) = board.null_move() {
+        val -= eval_mobility(&flipped);
+    }
+    let structure = pawn_table.probe(board);
+    val += PLAYER_SCALAR_MAP[board.side_to_move().to_index()]
+        * (MATERIAL_SCALE * eval_pawn_structure(board, &structure)
+            + eval_king_safety(board, &structure));
+    val
+}
+
+/// Full evaluation from the side to move's point of view. `lazy_eval` is the incrementally
+/// updated material and placement score, which is always kept from White's point of view.
+#[inline]
+pub fn evaluate(board: &Board, lazy_eval: Score, pawn_table: &mut PawnTable) -> i32 {
+    taper(
+        board,
+        PLAYER_SCALAR_MAP[board.side_to_move().to_index()] * MATERIAL_SCALE * lazy_eval
+            + lazy_assess_board(board, pawn_table),
+    )
+}
+
+pub fn assess_incremental(board: &Board, chess_move: ChessMove) -> Score {
+    let mut val = Score::ZERO;
+    let moving_piece = board.piece_on(chess_move.get_source()).unwrap();
+    let moving_val = &PIECE_VALUES[moving_piece.to_index()];
+    let result_piece = chess_move.get_promotion().unwrap_or(moving_piece);
+    let result_val = &PIECE_VALUES[result_piece.to_index()];
+    let captured_piece = board.piece_on(chess_move.get_dest());
+    let side_scalar = PLAYER_SCALAR_MAP[board.side_to_move().to_index()];
+    let current_player = board.side_to_move();
+    // Eval diff for moving forward (accounts for promotion)
+    val += eval_piece_position(result_piece, chess_move.get_dest(), current_player)
+        - eval_piece_position(moving_piece, chess_move.get_source(), current_player);
+    // Eval pure value diff for promoting
+    val += side_scalar * (result_val.value - moving_val.value);
+    // Eval diff for captures
+    if let Some(captured) = captured_piece {
+        val -= eval_piece(captured, chess_move.get_dest(), !board.side_to_move());
+    } else if let Some(en_passant_square) = board.en_passant() {
+        if moving_piece == Piece::Pawn
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file()
+        {
+            val -= eval_piece(Piece::Pawn, en_passant_square, !board.side_to_move());
         }
     }
-    mobility
+    // Eval diff for the rook when castling
+    let source_file = chess_move.get_source().get_file().to_index();
+    let dest_file = chess_move.get_dest().get_file().to_index();
+    if moving_piece == Piece::King && source_file.abs_diff(dest_file) == 2 {
+        let rank = chess_move.get_source().get_rank();
+        let (rook_source, rook_dest) = if dest_file > source_file {
+            (File::H, File::F)
+        } else {
+            (File::A, File::D)
+        };
+        val += eval_piece_position(
+            Piece::Rook,
+            Square::make_square(rank, rook_dest),
+            current_player,
+        ) - eval_piece_position(
+            Piece::Rook,
+            Square::make_square(rank, rook_source),
+            current_player,
+        );
+    }
+    val
 }
@@ -0,0 +1,67 @@
+use crate::constants::FIFTY_MOVE_PLIES;
+use chess::{Board, ChessMove, Piece};
+
+/// Hashes and fifty-move counters for every position of the game and of the current search path,
+/// which `chess::Board` does not track itself
+#[derive(Clone)]
+pub struct PositionHistory {
+    hashes: Vec<u64>,
+    halfmove_clocks: Vec<u16>,
+}
+
+impl PositionHistory {
+    pub fn new(board: &Board, halfmove_clock: u16) -> PositionHistory {
+        PositionHistory {
+            hashes: vec![board.get_hash()],
+            halfmove_clocks: vec![halfmove_clock],
+        }
+    }
+
+    /// Records the position reached by playing `chess_move` on `board`
+    pub fn push(&mut self, board: &Board, chess_move: ChessMove, new_board: &Board) {
+        let irreversible = board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            || board.piece_on(chess_move.get_dest()).is_some();
+        let halfmove_clock = if irreversible {
+            0
+        } else {
+            self.halfmove_clock() + 1
+        };
+        self.hashes.push(new_board.get_hash());
+        self.halfmove_clocks.push(halfmove_clock);
+    }
+
+    /// Records a null move. The fifty-move counter restarts so that repetitions are never detected
+    /// across a pass, which is not a legal move.
+    pub fn push_null(&mut self, new_board: &Board) {
+        self.hashes.push(new_board.get_hash());
+        self.halfmove_clocks.push(0);
+    }
+
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+        self.halfmove_clocks.pop();
+    }
+
+    pub fn halfmove_clock(&self) -> u16 {
+        *self.halfmove_clocks.last().unwrap_or(&0)
+    }
+
+    /// Whether the current position already occurred since the last irreversible move. A single
+    /// repetition is scored as a draw, since the side that allowed it can always repeat again.
+    pub fn is_repetition(&self) -> bool {
+        let Some((&current, previous)) = self.hashes.split_last() else {
+            return false;
+        };
+        previous
+            .iter()
+            .rev()
+            .take(self.halfmove_clock() as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|&hash| hash == current)
+    }
+
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock() >= FIFTY_MOVE_PLIES
+    }
+}
@@ -0,0 +1,134 @@
+use crate::constants::{
+    ATTACKER_COUNT_PERCENT, ATTACK_WEIGHT_MAP, BLACK_KING_DANGER_SQUARE_MAP, BLOCKED_STORM_PERCENT,
+    KING_DANGER_ENDGAME_PERCENT, KING_OPEN_FILE_PENALTY, KING_SEMI_OPEN_FILE_PENALTY,
+    MATERIAL_SCALE, PAWN_SHIELD_BONUS, PAWN_STORM_PENALTY, PIECE_VALUES, PLAYER_SCALAR_MAP,
+    SAFE_CHECK_UNITS, WHITE_KING_DANGER_SQUARE_MAP,
+};
+use crate::pawns::{forward_ranks, relative_rank, PawnStructure};
+use crate::score::Score;
+use chess::{
+    get_bishop_moves, get_file, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves,
+    BitBoard, Board, Color, Piece, Square, ALL_COLORS, ALL_FILES, ALL_PIECES, EMPTY,
+};
+use std::cmp::min;
+
+/// Squares attacked by `player`'s `piece` standing on `square`
+#[inline]
+fn piece_attacks(piece: Piece, square: Square, player: Color, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Pawn => get_pawn_attacks(square, player, !EMPTY),
+        Piece::Knight => get_knight_moves(square),
+        Piece::Bishop => get_bishop_moves(square, occupied),
+        Piece::Rook => get_rook_moves(square, occupied),
+        Piece::Queen => get_bishop_moves(square, occupied) | get_rook_moves(square, occupied),
+        Piece::King => get_king_moves(square),
+    }
+}
+
+/// Every square attacked by at least one of `player`'s pieces
+fn attacked_by(board: &Board, player: Color) -> BitBoard {
+    let mut attacked = EMPTY;
+    for piece in ALL_PIECES {
+        for square in board.pieces(piece) & board.color_combined(player) {
+            attacked |= piece_attacks(piece, square, player, *board.combined());
+        }
+    }
+    attacked
+}
+
+/// Shield, storm and open file terms on the king's file and the files beside it, from `player`'s
+/// point of view
+fn eval_king_cover(
+    board: &Board,
+    structure: &PawnStructure,
+    player: Color,
+    king_square: Square,
+) -> Score {
+    let pawns = board.pieces(Piece::Pawn);
+    let own = pawns & board.color_combined(player);
+    let enemy = pawns & board.color_combined(!player);
+    let heavy_enemies =
+        (board.pieces(Piece::Rook) | board.pieces(Piece::Queen)) & board.color_combined(!player);
+    let ahead = forward_ranks(player, king_square.get_rank());
+    let king_file = king_square.get_file().to_index();
+    let mut val = Score::ZERO;
+    for file in ALL_FILES {
+        if file.to_index().abs_diff(king_file) > 1 {
+            continue;
+        }
+        let span = ahead & get_file(file);
+        let shield = (own & span)
+            .map(|square| relative_rank(player, square))
+            .min()
+            .unwrap_or(0);
+        let storm = (enemy & span)
+            .map(|square| relative_rank(player, square))
+            .min()
+            .unwrap_or(0);
+        val += PAWN_SHIELD_BONUS[shield];
+        if shield != 0 && storm == shield + 1 {
+            val += PAWN_STORM_PENALTY[storm] * BLOCKED_STORM_PERCENT / 100;
+        } else {
+            val += PAWN_STORM_PENALTY[storm];
+        }
+        if heavy_enemies != EMPTY
+            && (structure.open_files[player.to_index()] & get_file(file)) != EMPTY
+        {
+            if (structure.open_files[(!player).to_index()] & get_file(file)) != EMPTY {
+                val += KING_OPEN_FILE_PENALTY;
+            } else {
+                val += KING_SEMI_OPEN_FILE_PENALTY;
+            }
+        }
+    }
+    val
+}
+
+/// Danger to `player`'s king from the enemy pieces aiming at the squares around it, from that
+/// player's point of view in search units. Each piece adds its attack weight for every zone square
+/// it attacks, the total is scaled down when few pieces take part, and each kind of piece that can
+/// give a check the defender cannot answer by capture adds more.
+fn eval_king_attack(board: &Board, player: Color, king_square: Square) -> Score {
+    let attacker = !player;
+    let zone = match player {
+        Color::White => WHITE_KING_DANGER_SQUARE_MAP[king_square.to_index()],
+        Color::Black => BLACK_KING_DANGER_SQUARE_MAP[king_square.to_index()],
+    };
+    let occupied = *board.combined();
+    let unsafe_squares = attacked_by(board, player) | board.color_combined(attacker);
+    let mut attackers = 0;
+    let mut units = 0;
+    let mut check_units = 0;
+    for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        let safe_checks = piece_attacks(piece, king_square, player, occupied) & !unsafe_squares;
+        let mut can_check = false;
+        for square in board.pieces(piece) & board.color_combined(attacker) {
+            let attacks = piece_attacks(piece, square, attacker, occupied);
+            let hits = (attacks & zone).popcnt() as usize;
+            if hits > 0 {
+                attackers += 1;
+                units += PIECE_VALUES[piece.to_index()].attack_weight as usize * hits;
+            }
+            can_check |= (attacks & safe_checks) != EMPTY;
+        }
+        if can_check {
+            check_units += SAFE_CHECK_UNITS[piece.to_index()];
+        }
+    }
+    let scale = ATTACKER_COUNT_PERCENT[min(attackers, ATTACKER_COUNT_PERCENT.len() - 1)];
+    let units = units * scale as usize / 100 + check_units;
+    let danger = ATTACK_WEIGHT_MAP[min(units, ATTACK_WEIGHT_MAP.len() - 1)];
+    Score::new(-danger, -danger * KING_DANGER_ENDGAME_PERCENT / 100)
+}
+
+/// King safety of both sides from White's point of view, in search units
+pub fn eval_king_safety(board: &Board, structure: &PawnStructure) -> Score {
+    let mut val = Score::ZERO;
+    for player in ALL_COLORS {
+        let king_square = board.king_square(player);
+        val += PLAYER_SCALAR_MAP[player.to_index()]
+            * (MATERIAL_SCALE * eval_king_cover(board, structure, player, king_square)
+                + eval_king_attack(board, player, king_square));
+    }
+    val
+}
@@ -0,0 +1,127 @@
+use crate::constants::{
+    BAD_CAPTURE_SCORE, GOOD_CAPTURE_SCORE, HISTORY_MAX, KILLER_SCORE, MAX_PLY, PIECE_VALUES,
+    TT_MOVE_SCORE,
+};
+use crate::see::see;
+use chess::{Board, ChessMove, Color, MoveGen, Piece};
+use std::cmp::min;
+
+/// Killer moves per ply and a from-to history table per colour, filled in by quiet moves that
+/// caused beta cutoffs
+pub struct OrderingTables {
+    killers: Vec<[Option<ChessMove>; 2]>,
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl OrderingTables {
+    pub fn new() -> OrderingTables {
+        OrderingTables {
+            killers: vec![[None; 2]; MAX_PLY],
+            history: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    pub fn killers(&self, ply: u16) -> [Option<ChessMove>; 2] {
+        self.killers.get(ply as usize).copied().unwrap_or([None; 2])
+    }
+
+    pub fn store_killer(&mut self, ply: u16, chess_move: ChessMove) {
+        if let Some(slots) = self.killers.get_mut(ply as usize) {
+            if slots[0] != Some(chess_move) {
+                slots[1] = slots[0];
+                slots[0] = Some(chess_move);
+            }
+        }
+    }
+
+    pub fn history_score(&self, side: Color, chess_move: ChessMove) -> i32 {
+        self.history[side.to_index()][chess_move.get_source().to_index()]
+            [chess_move.get_dest().to_index()]
+    }
+
+    /// Rewards a quiet move that caused a cutoff by the square of the remaining draft, decaying
+    /// the existing score so the table stays within `HISTORY_MAX`
+    pub fn update_history(&mut self, side: Color, chess_move: ChessMove, draft: u16) {
+        let bonus = min(draft as i32 * draft as i32, HISTORY_MAX);
+        let entry = &mut self.history[side.to_index()][chess_move.get_source().to_index()]
+            [chess_move.get_dest().to_index()];
+        *entry += bonus - *entry * bonus / HISTORY_MAX;
+    }
+}
+
+#[inline]
+fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some()
+        || (board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file())
+}
+
+/// Whether a move is quiet, and so eligible for the killer and history heuristics
+#[inline]
+pub fn is_quiet(board: &Board, chess_move: ChessMove) -> bool {
+    !is_capture(board, chess_move) && chess_move.get_promotion().is_none()
+}
+
+/// Most valuable victim first, breaking ties with the least valuable attacker
+#[inline]
+fn mvv_lva(board: &Board, chess_move: ChessMove) -> i32 {
+    let victim = board.piece_on(chess_move.get_dest()).unwrap_or(Piece::Pawn);
+    let attacker = board.piece_on(chess_move.get_source()).unwrap();
+    let promotion = chess_move
+        .get_promotion()
+        .map_or(0, |piece| PIECE_VALUES[piece.to_index()].value.mg);
+    8 * (PIECE_VALUES[victim.to_index()].value.mg + promotion)
+        - PIECE_VALUES[attacker.to_index()].value.mg
+}
+
+/// Yields every legal move exactly once: the transposition table move, captures that do not lose
+/// material by MVV-LVA, killer moves, quiet moves by history score, and finally losing captures
+pub struct MovePicker {
+    moves: Vec<(i32, ChessMove)>,
+}
+
+impl MovePicker {
+    pub fn new(
+        board: &Board,
+        tt_move: Option<ChessMove>,
+        ply: u16,
+        tables: &OrderingTables,
+    ) -> MovePicker {
+        let killers = tables.killers(ply);
+        let moves = MoveGen::new_legal(board)
+            .map(|chess_move| {
+                let score = if Some(chess_move) == tt_move {
+                    TT_MOVE_SCORE
+                } else if !is_quiet(board, chess_move) {
+                    if see(board, chess_move) >= 0 {
+                        GOOD_CAPTURE_SCORE + mvv_lva(board, chess_move)
+                    } else {
+                        BAD_CAPTURE_SCORE + mvv_lva(board, chess_move)
+                    }
+                } else if killers[0] == Some(chess_move) {
+                    KILLER_SCORE + 1
+                } else if killers[1] == Some(chess_move) {
+                    KILLER_SCORE
+                } else {
+                    tables.history_score(board.side_to_move(), chess_move)
+                };
+                (score, chess_move)
+            })
+            .collect();
+        MovePicker { moves }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = ChessMove;
+
+    /// Selection sort, since a cutoff usually happens long before every move has been tried
+    fn next(&mut self) -> Option<ChessMove> {
+        let (best_index, _) = self
+            .moves
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (score, _))| *score)?;
+        Some(self.moves.swap_remove(best_index).1)
+    }
+}
@@ -0,0 +1,183 @@
+use crate::constants::{
+    BACKWARD_PAWN_PENALTY, BLOCKADED_PASSER_PERCENT, CANDIDATE_PASSER_BONUS, CONNECTED_PAWN_BONUS,
+    DOUBLED_PAWN_PENALTY, ISOLATED_PAWN_PENALTY, PASSED_PAWN_BONUS, PLAYER_SCALAR_MAP,
+};
+use crate::score::Score;
+use chess::{
+    get_adjacent_files, get_file, get_pawn_attacks, get_rank, BitBoard, Board, Color, Piece, Rank,
+    Square, ALL_COLORS, ALL_FILES, EMPTY,
+};
+
+/// Entries in each thread's pawn table. Pawn structures repeat so often within a search that a
+/// small table already serves nearly every probe.
+const PAWN_TABLE_ENTRIES: usize = 1 << 14;
+
+/// Every square on the ranks strictly in front of `rank`, as seen by `player`
+#[inline]
+pub fn forward_ranks(player: Color, rank: Rank) -> BitBoard {
+    let rank = rank.to_index() as u32;
+    BitBoard(match player {
+        Color::White => u64::MAX.checked_shl(8 * (rank + 1)).unwrap_or(0),
+        Color::Black => (1 << (8 * rank)) - 1,
+    })
+}
+
+/// Rank of `square` counted from `player`'s side of the board
+#[inline]
+pub fn relative_rank(player: Color, square: Square) -> usize {
+    match player {
+        Color::White => square.get_rank().to_index(),
+        Color::Black => 7 - square.get_rank().to_index(),
+    }
+}
+
+/// Finalizer of splitmix64, which spreads every input bit over the whole output
+#[inline]
+fn mix(mut bits: u64) -> u64 {
+    bits = (bits ^ (bits >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D049BB133111EB);
+    bits ^ (bits >> 31)
+}
+
+/// Hash of the pawns alone. `Board::get_pawn_hash` is never filled in by the chess crate, so the
+/// key is derived from the pawn bitboards instead.
+#[inline]
+fn pawn_key(board: &Board) -> u64 {
+    let pawns = board.pieces(Piece::Pawn);
+    let white = pawns & board.color_combined(Color::White);
+    let black = pawns & board.color_combined(Color::Black);
+    mix(white.0 ^ mix(black.0))
+}
+
+/// Everything about the pawn structure that depends on the pawns alone
+#[derive(Clone, Copy)]
+pub struct PawnStructure {
+    /// Isolated, doubled, backward, connected and candidate pawn terms, from White's point of view
+    pub score: Score,
+    /// Passed pawns by colour, whose bonus also depends on the pieces blockading them
+    pub passed: [BitBoard; 2],
+    /// Files holding none of that colour's pawns. Files open for both sides are the intersection.
+    pub open_files: [BitBoard; 2],
+    /// Every square that colour's pawns attack now or could attack by advancing
+    pub attack_spans: [BitBoard; 2],
+}
+
+impl PawnStructure {
+    pub fn new(board: &Board) -> PawnStructure {
+        let pawns = board.pieces(Piece::Pawn);
+        let mut structure = PawnStructure {
+            score: Score::ZERO,
+            passed: [EMPTY; 2],
+            open_files: [EMPTY; 2],
+            attack_spans: [EMPTY; 2],
+        };
+        for player in ALL_COLORS {
+            let own = pawns & board.color_combined(player);
+            for file in ALL_FILES {
+                if (own & get_file(file)) == EMPTY {
+                    structure.open_files[player.to_index()] |= get_file(file);
+                }
+            }
+            for square in own {
+                structure.attack_spans[player.to_index()] |=
+                    forward_ranks(player, square.get_rank())
+                        & get_adjacent_files(square.get_file());
+            }
+        }
+        for player in ALL_COLORS {
+            let own = pawns & board.color_combined(player);
+            let enemy = pawns & board.color_combined(!player);
+            let open_files = structure.open_files[player.to_index()];
+            let attack_span = structure.attack_spans[player.to_index()];
+            let mut score = Score::ZERO;
+            for square in own {
+                let rank = relative_rank(player, square);
+                let file = get_file(square.get_file());
+                let adjacent = get_adjacent_files(square.get_file());
+                let ahead = forward_ranks(player, square.get_rank());
+                let front_span = ahead & file;
+                let passed_span = ahead & (file | adjacent);
+                // Only the frontmost of doubled pawns can be passed
+                if (enemy & passed_span) == EMPTY && (own & front_span) == EMPTY {
+                    structure.passed[player.to_index()] |= BitBoard::from_square(square);
+                } else if (enemy & front_span) == EMPTY {
+                    // A candidate passer has an open file ahead and at least as many friendly
+                    // pawns beside or behind it to help it through as there are enemy sentries
+                    let helpers = own & adjacent & !ahead;
+                    let sentries = enemy & passed_span;
+                    if helpers.popcnt() >= sentries.popcnt() {
+                        score += CANDIDATE_PASSER_BONUS[rank];
+                    }
+                }
+                if (own & front_span) != EMPTY {
+                    score += DOUBLED_PAWN_PENALTY;
+                }
+                let supporters = get_pawn_attacks(square, !player, own);
+                let phalanx = own & adjacent & get_rank(square.get_rank());
+                if (adjacent & !open_files) == EMPTY {
+                    score += ISOLATED_PAWN_PENALTY;
+                } else if (supporters | phalanx) != EMPTY {
+                    score += CONNECTED_PAWN_BONUS[rank];
+                } else if (attack_span & BitBoard::from_square(square)) == EMPTY
+                    && square
+                        .forward(player)
+                        .is_some_and(|stop| get_pawn_attacks(stop, player, enemy) != EMPTY)
+                {
+                    // Every neighbour has already advanced past it, so it cannot be supported
+                    // and cannot safely advance either
+                    score += BACKWARD_PAWN_PENALTY;
+                }
+            }
+            structure.score += PLAYER_SCALAR_MAP[player.to_index()] * score;
+        }
+        structure
+    }
+}
+
+/// Per-thread cache of pawn structures, keyed by the placement of the pawns alone. Each thread
+/// owns its own, so no locking is needed.
+pub struct PawnTable {
+    entries: Box<[Option<(u64, PawnStructure)>]>,
+}
+
+impl PawnTable {
+    pub fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![None; PAWN_TABLE_ENTRIES].into_boxed_slice(),
+        }
+    }
+
+    /// The structure of `board`'s pawns, computed only if it is not already cached
+    pub fn probe(&mut self, board: &Board) -> PawnStructure {
+        let key = pawn_key(board);
+        let entry = &mut self.entries[key as usize % PAWN_TABLE_ENTRIES];
+        match *entry {
+            Some((stored, structure)) if stored == key => structure,
+            _ => {
+                let structure = PawnStructure::new(board);
+                *entry = Some((key, structure));
+                structure
+            }
+        }
+    }
+}
+
+/// Full pawn structure score from White's point of view. Passed pawns are worth more the further
+/// they have advanced, and less while a piece blockades the square in front of them, which is why
+/// that part is not cached.
+pub fn eval_pawn_structure(board: &Board, structure: &PawnStructure) -> Score {
+    let mut val = structure.score;
+    for player in ALL_COLORS {
+        for square in structure.passed[player.to_index()] {
+            let mut bonus = PASSED_PAWN_BONUS[relative_rank(player, square)];
+            let blockaded = square
+                .forward(player)
+                .is_some_and(|stop| board.piece_on(stop).is_some());
+            if blockaded {
+                bonus = bonus * BLOCKADED_PASSER_PERCENT / 100;
+            }
+            val += PLAYER_SCALAR_MAP[player.to_index()] * bonus;
+        }
+    }
+    val
+}
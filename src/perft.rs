@@ -0,0 +1,120 @@
+use chess::{Board, ChessMove, MoveGen};
+
+/// Counts the leaf nodes of the legal move tree `depth` plies below `board`
+pub fn perft(board: &Board, depth: u16) -> u64 {
+    match depth {
+        0 => 1,
+        // Bulk counting: the leaves are exactly the legal moves of the last ply
+        1 => MoveGen::new_legal(board).len() as u64,
+        _ => MoveGen::new_legal(board)
+            .map(|chess_move| perft(&board.make_move_new(chess_move), depth - 1))
+            .sum(),
+    }
+}
+
+/// Perft split by root move, for narrowing a miscount down to the line that causes it
+pub fn divide(board: &Board, depth: u16) -> Vec<(ChessMove, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    MoveGen::new_legal(board)
+        .map(|chess_move| {
+            (
+                chess_move,
+                perft(&board.make_move_new(chess_move), depth - 1),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{assess_incremental, eval_all_pieces_positional};
+    use crate::score::Score;
+    use std::str::FromStr;
+
+    /// Standard positions and their known node counts by depth, from the Chess Programming Wiki
+    const PERFT_POSITIONS: [(&str, &[u64]); 6] = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8902, 197281],
+        ),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        ),
+        (
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43238],
+        ),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        ),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486, 62379],
+        ),
+        (
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079, 89890],
+        ),
+    ];
+
+    #[test]
+    fn perft_matches_known_counts() {
+        for (fen, counts) in PERFT_POSITIONS {
+            let board = Board::from_str(fen).unwrap();
+            for (depth, &count) in counts.iter().enumerate() {
+                assert_eq!(
+                    perft(&board, depth as u16 + 1),
+                    count,
+                    "{} at depth {}",
+                    fen,
+                    depth + 1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        for (fen, counts) in PERFT_POSITIONS {
+            let board = Board::from_str(fen).unwrap();
+            let split = divide(&board, 2);
+            assert_eq!(split.len() as u64, counts[0], "{}", fen);
+            assert_eq!(
+                split.iter().map(|(_, nodes)| nodes).sum::<u64>(),
+                counts[1],
+                "{}",
+                fen
+            );
+        }
+    }
+
+    /// Walks the same trees as perft, checking that the incremental evaluation used by the search
+    /// never drifts from a full recomputation, including through castling, en passant and
+    /// promotions
+    fn assert_incremental_eval(board: &Board, lazy_eval: Score, depth: u16) {
+        assert_eq!(lazy_eval, eval_all_pieces_positional(board), "{}", board);
+        if depth == 0 {
+            return;
+        }
+        for chess_move in MoveGen::new_legal(board) {
+            assert_incremental_eval(
+                &board.make_move_new(chess_move),
+                lazy_eval + assess_incremental(board, chess_move),
+                depth - 1,
+            );
+        }
+    }
+
+    #[test]
+    fn incremental_eval_matches_full_eval() {
+        for (fen, _) in PERFT_POSITIONS {
+            let board = Board::from_str(fen).unwrap();
+            assert_incremental_eval(&board, eval_all_pieces_positional(&board), 3);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::constants::{EG_PIECE_SQUARE_TABLES, MG_PIECE_SQUARE_TABLES};
+use crate::score::Score;
+use chess::{Color, Piece, Square, NUM_PIECES, NUM_SQUARES};
+use std::fs::read_to_string;
+use std::ptr::addr_of;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+pub type PieceSquareTables = [[Score; NUM_SQUARES]; NUM_PIECES];
+
+const fn combine(
+    mg: &[[i32; NUM_SQUARES]; NUM_PIECES],
+    eg: &[[i32; NUM_SQUARES]; NUM_PIECES],
+) -> PieceSquareTables {
+    let mut tables = [[Score::ZERO; NUM_SQUARES]; NUM_PIECES];
+    let mut piece = 0;
+    while piece < NUM_PIECES {
+        let mut square = 0;
+        while square < NUM_SQUARES {
+            tables[piece][square] = Score::new(mg[piece][square], eg[piece][square]);
+            square += 1;
+        }
+        piece += 1;
+    }
+    tables
+}
+
+static DEFAULT_TABLES: PieceSquareTables =
+    combine(&MG_PIECE_SQUARE_TABLES, &EG_PIECE_SQUARE_TABLES);
+
+/// The tables the evaluation reads. Loaded tables are leaked rather than freed when replaced, so
+/// that a reference taken by a running search can never dangle.
+static ACTIVE_TABLES: AtomicPtr<PieceSquareTables> =
+    AtomicPtr::new(addr_of!(DEFAULT_TABLES).cast_mut());
+
+/// Placement bonus for `player`'s `piece` on `square`, from that player's point of view
+#[inline]
+pub fn piece_square(piece: Piece, square: Square, player: Color) -> Score {
+    // SAFETY: the pointer is always either the static defaults or a leaked, never freed table
+    let tables = unsafe { &*ACTIVE_TABLES.load(Ordering::Acquire) };
+    let index = match player {
+        Color::White => square.to_index() ^ 56,
+        Color::Black => square.to_index(),
+    };
+    tables[piece.to_index()][index]
+}
+
+pub fn reset_piece_square_tables() {
+    ACTIVE_TABLES.store(addr_of!(DEFAULT_TABLES).cast_mut(), Ordering::Release);
+}
+
+/// Replaces the tables with ones read from `path`. The file holds 768 whitespace separated
+/// integers, with `#` starting a comment: for each piece from pawn to king, a middlegame then an
+/// endgame table of 64 squares, laid out like `MG_PIECE_SQUARE_TABLES`.
+pub fn load_piece_square_tables(path: &str) -> Result<(), String> {
+    let contents = read_to_string(path).map_err(|error| error.to_string())?;
+    let values = contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(str::split_whitespace)
+        .map(|token| {
+            token
+                .trim_end_matches(',')
+                .parse::<i32>()
+                .map_err(|error| format!("{} | {}", token, error))
+        })
+        .collect::<Result<Vec<i32>, String>>()?;
+    let expected = 2 * NUM_PIECES * NUM_SQUARES;
+    if values.len() != expected {
+        return Err(format!(
+            "expected {} values, found {}",
+            expected,
+            values.len()
+        ));
+    }
+    let mut tables = [[Score::ZERO; NUM_SQUARES]; NUM_PIECES];
+    for (piece, table) in tables.iter_mut().enumerate() {
+        let mg = &values[2 * piece * NUM_SQUARES..];
+        let eg = &mg[NUM_SQUARES..];
+        for (square, score) in table.iter_mut().enumerate() {
+            *score = Score::new(mg[square], eg[square]);
+        }
+    }
+    ACTIVE_TABLES.store(Box::leak(Box::new(tables)), Ordering::Release);
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A middlegame and an endgame value for the same evaluation term, which are blended by the game
+/// phase only once the whole evaluation has been summed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const ZERO: Score = Score::new(0, 0);
+
+    pub const fn new(mg: i32, eg: i32) -> Score {
+        Score { mg, eg }
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, scalar: i32) -> Score {
+        Score::new(self.mg * scalar, self.eg * scalar)
+    }
+}
+
+impl Mul<Score> for i32 {
+    type Output = Score;
+
+    fn mul(self, score: Score) -> Score {
+        score * self
+    }
+}
+
+impl Div<i32> for Score {
+    type Output = Score;
+
+    fn div(self, divisor: i32) -> Score {
+        Score::new(self.mg / divisor, self.eg / divisor)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
@@ -0,0 +1,802 @@
+use crate::constants::*;
+use crate::eval::{
+    assess_incremental, eval_all_pieces_positional, evaluate, is_insufficient_material, taper,
+};
+use crate::history::PositionHistory;
+use crate::movepick::{is_quiet, MovePicker, OrderingTables};
+use crate::pawns::PawnTable;
+use crate::score::Score;
+use crate::search::NodeType::{All, Cut, PV};
+use crate::see::see;
+use crate::timeman::TimeManager;
+use crate::tt::TranspositionTable;
+use crate::uci::send_search_info;
+use chess::{get_rank, BitBoard, Board, BoardStatus, ChessMove, MoveGen, Piece};
+use std::cmp::{max, min};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::scope;
+
+/// How a stored value relates to the true value of its position, judged against the window the
+/// node was searched with
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum NodeType {
+    /// Exact value, strictly inside the window
+    PV,
+    /// Lower bound, from a beta cutoff
+    Cut,
+    /// Upper bound, since no move raised alpha
+    All,
+}
+
+/// How the root is searched at each depth of iterative deepening
+#[derive(Clone, Copy, PartialEq)]
+pub enum RootAlgorithm {
+    Mtdf,
+    Pvs,
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub struct SearchResult {
+    pub value: i32,
+    pub best_move: Option<ChessMove>,
+    /// Remaining draft the value was searched with, where 0 is a quiescence search
+    pub depth: u16,
+    pub node_type: NodeType,
+}
+
+impl SearchResult {
+    pub fn new(value: i32, best_move: Option<ChessMove>, depth: u16) -> SearchResult {
+        SearchResult {
+            value,
+            best_move,
+            depth,
+            node_type: PV,
+        }
+    }
+}
+
+/// Mate scores are stored relative to the cached node rather than the root, so that a cached mate is
+/// still reported at the right distance when the position is reached along a different path
+#[inline]
+fn value_to_cache(value: i32, ply: u16) -> i32 {
+    if value >= MATE_BOUND {
+        value + ply as i32
+    } else if value <= -MATE_BOUND {
+        value - ply as i32
+    } else {
+        value
+    }
+}
+
+#[inline]
+fn value_from_cache(value: i32, ply: u16) -> i32 {
+    if value >= MATE_BOUND {
+        value - ply as i32
+    } else if value <= -MATE_BOUND {
+        value + ply as i32
+    } else {
+        value
+    }
+}
+
+#[derive(Default)]
+pub struct SearchStats {
+    pub nodes: u64,
+    pub seldepth: u16,
+}
+
+/// A position on the current search path. `logical_depth` advances more slowly than `true_depth`
+/// (the distance from the root) when a move is extended, and faster when it is reduced.
+#[derive(Clone, Copy)]
+struct SearchNode {
+    board: Board,
+    logical_depth: u16,
+    true_depth: u16,
+    lazy_eval: Score,
+}
+
+impl SearchNode {
+    fn root(board: Board) -> SearchNode {
+        SearchNode {
+            board,
+            logical_depth: MAX_DEPTH_INCREASE,
+            true_depth: 0,
+            lazy_eval: eval_all_pieces_positional(&board),
+        }
+    }
+
+    /// The node reached by playing `chess_move`, searched as if `logical_depth` plies deep
+    fn child(&self, chess_move: ChessMove, new_board: Board, logical_depth: u16) -> SearchNode {
+        SearchNode {
+            board: new_board,
+            logical_depth,
+            true_depth: self.true_depth + 1,
+            lazy_eval: self.lazy_eval + assess_incremental(&self.board, chess_move),
+        }
+    }
+}
+
+/// Everything a single search thread needs besides the position: the shared transposition table
+/// and time manager, plus the move ordering tables, statistics and game history it owns
+pub struct Searcher<'a> {
+    memo_table: &'a TranspositionTable,
+    time_manager: &'a TimeManager,
+    history: PositionHistory,
+    ordering: OrderingTables,
+    pawn_table: PawnTable,
+    stats: SearchStats,
+    depth_limit: u16,
+    thread_id: usize,
+    abort: Option<&'a AtomicBool>,
+    /// Node count of every thread of a Lazy SMP search, which each thread adds to in batches
+    shared_nodes: Option<&'a AtomicU64>,
+    /// Whether pruning, reductions and extensions are enabled. Without them the search is plain
+    /// alpha-beta, whose value does not depend on move ordering or on what the table holds.
+    selective: bool,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(
+        memo_table: &'a TranspositionTable,
+        time_manager: &'a TimeManager,
+        history: PositionHistory,
+    ) -> Searcher<'a> {
+        Searcher {
+            memo_table,
+            time_manager,
+            history,
+            ordering: OrderingTables::new(),
+            pawn_table: PawnTable::new(),
+            stats: SearchStats::default(),
+            depth_limit: 0,
+            thread_id: 0,
+            abort: None,
+            shared_nodes: None,
+            selective: true,
+        }
+    }
+
+    /// A Lazy SMP helper, which searches silently until the time manager runs out or `abort` is
+    /// set by the main thread
+    fn helper(
+        memo_table: &'a TranspositionTable,
+        time_manager: &'a TimeManager,
+        history: PositionHistory,
+        thread_id: usize,
+        abort: &'a AtomicBool,
+        shared_nodes: &'a AtomicU64,
+    ) -> Searcher<'a> {
+        Searcher {
+            thread_id,
+            abort: Some(abort),
+            shared_nodes: Some(shared_nodes),
+            ..Searcher::new(memo_table, time_manager, history)
+        }
+    }
+
+    #[inline]
+    fn count_node(&mut self) {
+        self.stats.nodes += 1;
+        if self.stats.nodes.is_multiple_of(NODE_COUNT_BATCH) {
+            if let Some(shared_nodes) = self.shared_nodes {
+                shared_nodes.fetch_add(NODE_COUNT_BATCH, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Nodes searched so far by every thread, short of the batches other threads have not yet
+    /// added to the shared count
+    fn total_nodes(&self) -> u64 {
+        match self.shared_nodes {
+            Some(shared_nodes) => {
+                shared_nodes.load(Ordering::Relaxed) + self.stats.nodes % NODE_COUNT_BATCH
+            }
+            None => self.stats.nodes,
+        }
+    }
+
+    #[inline]
+    fn hard_expired(&self) -> bool {
+        self.time_manager.hard_expired()
+            || self
+                .abort
+                .is_some_and(|abort| abort.load(Ordering::Relaxed))
+    }
+
+    /// Searches every depth from 1 up to `max_depth`, returning the result of the deepest iteration
+    /// that completed before the time manager ran out. Each iteration's value seeds the next MTD(f)
+    /// guess, and a UCI `info` line is printed per completed depth.
+    pub fn iterative_deepening(
+        &mut self,
+        board: &Board,
+        max_depth: u16,
+        algorithm: RootAlgorithm,
+    ) -> SearchResult {
+        let mut best = SearchResult::new(0, None, 0);
+        let mut guess = None;
+        // Helpers skip alternate first iterations, so that threads spread over neighbouring depths
+        // instead of all searching the same tree in lockstep
+        let first_depth = 1 + (self.thread_id % 2) as u16;
+        for depth in first_depth..=max_depth {
+            self.stats.seldepth = 0;
+            let result = self.start_search(board, depth, guess, algorithm);
+            if self.hard_expired() {
+                // A partially searched root is still better than having no move at all
+                if best.best_move.is_none() {
+                    best = result;
+                }
+                break;
+            }
+            best = result;
+            guess = Some(result.value);
+            if self.thread_id == 0 {
+                send_search_info(
+                    depth,
+                    self.stats.seldepth,
+                    &result,
+                    &extract_pv(board, self.memo_table),
+                    self.total_nodes(),
+                    self.time_manager.elapsed(),
+                );
+            }
+            if self.time_manager.soft_expired() {
+                break;
+            }
+        }
+        if best.best_move.is_none() {
+            best.best_move = MoveGen::new_legal(board).next();
+        }
+        best
+    }
+
+    /// Searches the root at a fixed depth with the selected algorithm, starting from `guess` or the
+    /// cached root value if there is none
+    pub fn start_search(
+        &mut self,
+        board: &Board,
+        depth: u16,
+        guess: Option<i32>,
+        algorithm: RootAlgorithm,
+    ) -> SearchResult {
+        let root = SearchNode::root(*board);
+        let guess = guess.or_else(|| self.memo_table.get(board.get_hash()).map(|prev| prev.value));
+        self.depth_limit = depth + MAX_DEPTH_INCREASE;
+        let mut best: SearchResult = SearchResult {
+            value: 0,
+            best_move: None,
+            depth,
+            node_type: NodeType::PV,
+        };
+        match algorithm {
+            RootAlgorithm::Mtdf => {
+                // Zero-window searches converging on the minimax value from both sides
+                let mut upper_bound = INFINITY;
+                let mut lower_bound = -INFINITY;
+                let mut guess = guess.unwrap_or(0);
+                let mut beta: i32;
+                while lower_bound < upper_bound {
+                    beta = guess + (guess == lower_bound) as i32;
+                    best = self.search(root, beta - 1, beta, true);
+                    if self.hard_expired() {
+                        break;
+                    }
+                    guess = best.value;
+                    lower_bound =
+                        (guess < beta) as i32 * lower_bound + (guess >= beta) as i32 * guess;
+                    upper_bound =
+                        (guess < beta) as i32 * guess + (guess >= beta) as i32 * upper_bound;
+                }
+            }
+            RootAlgorithm::Pvs => {
+                // Aspiration window around the guess, widened on the failing side until the value
+                // lands inside it
+                let mut delta = ASPIRATION_WINDOW;
+                let (mut alpha, mut beta) = match guess {
+                    Some(guess) => (max(guess - delta, -INFINITY), min(guess + delta, INFINITY)),
+                    None => (-INFINITY, INFINITY),
+                };
+                loop {
+                    best = self.search(root, alpha, beta, true);
+                    if self.hard_expired() {
+                        break;
+                    }
+                    if best.value <= alpha && alpha > -INFINITY {
+                        alpha = max(best.value - delta, -INFINITY);
+                    } else if best.value >= beta && beta < INFINITY {
+                        beta = min(best.value + delta, INFINITY);
+                    } else {
+                        break;
+                    }
+                    delta *= 2;
+                }
+            }
+        }
+        best
+    }
+
+    /// Resolves captures (and checks for the first few plies) below the horizon, so that the
+    /// static evaluation is only trusted in quiet positions. The side to move may always stand pat
+    /// instead of capturing, unless it is in check.
+    fn quiesce(
+        &mut self,
+        node: SearchNode,
+        quiescence_depth: u16,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.hard_expired() {
+            return 0;
+        }
+        let board = node.board;
+        self.count_node();
+        self.stats.seldepth = max(self.stats.seldepth, node.true_depth);
+        let in_check = board.checkers().0 > 0;
+        let mut moves = MoveGen::new_legal(&board);
+        if moves.len() == 0 {
+            return if in_check {
+                node.true_depth as i32 - MATE_SCORE
+            } else {
+                DRAW_SCORE
+            };
+        }
+        let mut best = -INFINITY;
+        let mut candidates: Vec<ChessMove> = Vec::new();
+        if in_check {
+            candidates.extend(&mut moves);
+        } else {
+            best = evaluate(&board, node.lazy_eval, &mut self.pawn_table);
+            if best >= beta {
+                return best;
+            }
+            alpha = max(alpha, best);
+            let player = board.side_to_move();
+            let mut targets = *board.color_combined(!player) | get_rank(player.to_their_backrank());
+            if let Some(dest) = board.en_passant().and_then(|square| square.forward(player)) {
+                targets |= BitBoard::from_square(dest);
+            }
+            moves.set_iterator_mask(targets);
+            // Pieces other than pawns may also step quietly onto the target squares
+            let (mut quiets, tactical): (Vec<ChessMove>, Vec<ChessMove>) =
+                (&mut moves).partition(|mov| is_quiet(&board, *mov));
+            // Captures and promotions that lose material by exchange cannot raise the stand pat
+            // score
+            let mut captures: Vec<(i32, ChessMove)> = tactical
+                .into_iter()
+                .map(|capture| (see(&board, capture), capture))
+                .filter(|(exchange, _)| *exchange >= 0)
+                .collect();
+            captures.sort_by_key(|(exchange, _)| -exchange);
+            candidates.extend(captures.into_iter().map(|(_, capture)| capture));
+            if quiescence_depth < QUIESCENCE_CHECK_PLIES {
+                moves.set_iterator_mask(!chess::EMPTY);
+                quiets.extend(&mut moves);
+                candidates.extend(
+                    quiets
+                        .into_iter()
+                        .filter(|quiet| board.make_move_new(*quiet).checkers().0 > 0),
+                );
+            }
+        }
+        for mov in candidates {
+            let child = node.child(mov, board.make_move_new(mov), node.logical_depth);
+            let value = -self.quiesce(child, quiescence_depth + 1, -beta, -alpha);
+            if self.hard_expired() {
+                return best;
+            }
+            best = max(best, value);
+            if best >= beta {
+                break;
+            }
+            alpha = max(alpha, best);
+        }
+        best
+    }
+
+    /// Negamax alpha-beta search. Values are relative to the side to move, so a child's value is
+    /// negated and its window mirrored.
+    fn search(
+        &mut self,
+        node: SearchNode,
+        alpha: i32,
+        beta: i32,
+        allow_null: bool,
+    ) -> SearchResult {
+        let SearchNode {
+            board,
+            logical_depth,
+            true_depth,
+            lazy_eval,
+        } = node;
+        let depth_limit = self.depth_limit;
+        if self.hard_expired() {
+            return SearchResult::new(0, None, true_depth);
+        }
+        self.count_node();
+        self.stats.seldepth = max(self.stats.seldepth, true_depth);
+        match board.status() {
+            BoardStatus::Ongoing => {}
+            BoardStatus::Stalemate => {
+                return SearchResult::new(DRAW_SCORE, None, 0);
+            }
+            BoardStatus::Checkmate => {
+                let mated_value = true_depth as i32 - MATE_SCORE;
+                return SearchResult::new(mated_value, None, 0);
+            }
+        }
+        if true_depth > 0
+            && (self.history.is_repetition()
+                || self.history.is_fifty_move_draw()
+                || is_insufficient_material(&board))
+        {
+            return SearchResult::new(DRAW_SCORE, None, true_depth);
+        }
+
+        // Extensions keep the logical depth flat while the distance from the root still grows, and
+        // the subtree is cut off by whichever of the two reaches the limit first
+        let draft = depth_limit.saturating_sub(max(logical_depth, true_depth));
+        let leaf = draft == 0;
+        let cached_result = self.memo_table.get(board.get_hash()).map(|mut cached| {
+            cached.value = value_from_cache(cached.value, true_depth);
+            cached
+        });
+        if let Some(result) = cached_result {
+            // The root is always searched, so that every iteration of iterative deepening produces
+            // a fresh best move rather than replaying a cached bound
+            if true_depth > 0 && result.depth >= draft {
+                let cutoff = match result.node_type {
+                    PV => true,
+                    Cut => result.value >= beta,
+                    All => result.value <= alpha,
+                };
+                if cutoff {
+                    return result;
+                }
+            }
+        }
+        if leaf {
+            return SearchResult::new(self.quiesce(node, 0, alpha, beta), None, 0);
+        }
+        let static_eval = taper(
+            &board,
+            PLAYER_SCALAR_MAP[board.side_to_move().to_index()] * MATERIAL_SCALE * lazy_eval,
+        );
+        let has_pieces = (board.color_combined(board.side_to_move())
+            & !(board.pieces(Piece::Pawn) | board.pieces(Piece::King)))
+            != chess::EMPTY;
+        // Null move pruning: if passing still fails high at reduced depth, a real move will too.
+        // Pawn endgames are skipped since zugzwang makes passing better than any legal move there.
+        if self.selective
+            && allow_null
+            && true_depth > 0
+            && draft >= NULL_MOVE_MIN_DEPTH
+            && has_pieces
+            && static_eval >= beta
+        {
+            if let Some(null_board) = board.null_move() {
+                let reduction = NULL_MOVE_REDUCTION + draft / NULL_MOVE_REDUCTION_DIVISOR;
+                let null_node = SearchNode {
+                    board: null_board,
+                    logical_depth: min(logical_depth + 1 + reduction, depth_limit),
+                    true_depth: true_depth + 1,
+                    lazy_eval,
+                };
+                self.history.push_null(&null_board);
+                let null_value = -self.search(null_node, -beta, -beta + 1, false).value;
+                self.history.pop();
+                if self.hard_expired() {
+                    return SearchResult::new(0, None, true_depth);
+                }
+                if null_value >= beta {
+                    // Verify deep cutoffs with a reduced search of our own moves, without passing
+                    let verification_node = SearchNode {
+                        logical_depth: min(logical_depth + reduction, depth_limit),
+                        ..node
+                    };
+                    let verified = draft < NULL_MOVE_VERIFICATION_DEPTH
+                        || self.search(verification_node, beta - 1, beta, false).value >= beta;
+                    if verified && !self.hard_expired() {
+                        // Fail soft, so that MTD(f) can move its bound by more than one point at a
+                        // time, but mate scores found after passing are not trustworthy
+                        let value = min(null_value, MATE_BOUND - 1);
+                        return SearchResult::new(value, None, true_depth);
+                    }
+                }
+            }
+        }
+        let mut result = SearchResult::new(-INFINITY, None, draft);
+        let tt_move = cached_result.and_then(|cached| cached.best_move);
+        let in_check = board.checkers().0 > 0;
+        let mut a = alpha;
+        let mut moves_searched: usize = 0;
+        for mov in MovePicker::new(&board, tt_move, true_depth, &self.ordering) {
+            let new_board = board.make_move_new(mov);
+            let gives_check = new_board.checkers().0 > 0;
+            let late_quiet = self.selective
+                && is_quiet(&board, mov)
+                && !in_check
+                && !gives_check
+                && result.best_move.is_some()
+                && moves_searched >= LMR_MIN_MOVES_SEARCHED;
+            if late_quiet && draft <= LATE_MOVE_PRUNING_DEPTH && result.value > -MATE_BOUND {
+                // Near the leaves, late quiet moves are rarely worth searching at all, and never
+                // when even a generous positional swing leaves the material balance below alpha.
+                // While every move so far gets mated, the rest are searched for a defence.
+                let futile = static_eval + FUTILITY_MARGINS[draft as usize] <= a;
+                if futile || moves_searched >= LATE_MOVE_PRUNING_COUNTS[draft as usize] {
+                    continue;
+                }
+            }
+            let winning_capture = board.piece_on(mov.get_dest()).is_some() && see(&board, mov) > 0;
+            let new_depth = if self.selective && (gives_check || winning_capture) {
+                logical_depth
+            } else {
+                logical_depth + 1
+            };
+            // Late move reductions: search late quiet moves shallower first, and only at full depth
+            // if they unexpectedly improve on the best move so far
+            let mut reduction = if late_quiet && draft >= LMR_MIN_DEPTH {
+                LMR_REDUCTION + (moves_searched >= LMR_DEEP_MOVES_SEARCHED) as u16
+            } else {
+                0
+            };
+            self.history.push(&board, mov, &new_board);
+            // Principal variation search: after the first move, prove each move is no better with
+            // a zero window, and only search it with the full window if it is
+            let mut window = if moves_searched == 0 {
+                (a, beta)
+            } else {
+                (a, a + 1)
+            };
+            let value = loop {
+                let child = node.child(mov, new_board, min(new_depth + reduction, depth_limit));
+                let value = -self.search(child, -window.1, -window.0, true).value;
+                if value <= a || self.hard_expired() {
+                    break value;
+                }
+                if reduction > 0 {
+                    reduction = 0;
+                } else if window != (a, beta) {
+                    window = (a, beta);
+                } else {
+                    break value;
+                }
+            };
+            moves_searched += 1;
+            self.history.pop();
+            if self.hard_expired() {
+                // Abandon the node without caching a partially searched result
+                return result;
+            }
+            if value > result.value || result.best_move.is_none() {
+                result.value = value;
+                result.best_move = Some(mov);
+            }
+            if result.value >= beta {
+                if is_quiet(&board, mov) {
+                    self.ordering.store_killer(true_depth, mov);
+                    self.ordering
+                        .update_history(board.side_to_move(), mov, draft);
+                }
+                break;
+            }
+            a = max(a, value);
+        }
+        if result.value <= alpha {
+            result.node_type = All;
+        } else if result.value < beta {
+            result.node_type = PV;
+        } else {
+            result.node_type = Cut;
+        }
+        debug_assert!(
+            true_depth + draft <= depth_limit,
+            "draft {} stored {} plies from the root with a depth limit of {}",
+            draft,
+            true_depth,
+            depth_limit
+        );
+        let mut cached = result;
+        cached.value = value_to_cache(result.value, true_depth);
+        self.memo_table
+            .replace_if(board.get_hash(), cached, |old| old.depth <= result.depth);
+        result
+    }
+}
+
+/// Lazy SMP: `threads - 1` helpers search the same root as the main thread, sharing only the
+/// transposition table, so that their results steer the main thread's move ordering and cutoffs.
+/// Only the main thread reports, counting the nodes of every thread, and the helpers are stopped
+/// as soon as it finishes.
+pub fn lazy_smp(
+    board: &Board,
+    max_depth: u16,
+    algorithm: RootAlgorithm,
+    threads: usize,
+    memo_table: &TranspositionTable,
+    time_manager: &TimeManager,
+    history: PositionHistory,
+) -> SearchResult {
+    memo_table.new_search();
+    let abort = AtomicBool::new(false);
+    let shared_nodes = AtomicU64::new(0);
+    scope(|helpers| {
+        for thread_id in 1..threads {
+            let history = history.clone();
+            let abort = &abort;
+            let shared_nodes = &shared_nodes;
+            helpers.spawn(move || {
+                Searcher::helper(
+                    memo_table,
+                    time_manager,
+                    history,
+                    thread_id,
+                    abort,
+                    shared_nodes,
+                )
+                .iterative_deepening(board, max_depth, algorithm);
+            });
+        }
+        let mut searcher = Searcher::new(memo_table, time_manager, history);
+        searcher.shared_nodes = Some(&shared_nodes);
+        let result = searcher.iterative_deepening(board, max_depth, algorithm);
+        abort.store(true, Ordering::Relaxed);
+        result
+    })
+}
+
+/// Reconstructs the principal variation by following cached best moves from `board`, stopping at
+/// the first missing or illegal move, or once a position repeats
+pub fn extract_pv(board: &Board, memo_table: &TranspositionTable) -> Vec<ChessMove> {
+    let mut pv = Vec::new();
+    let mut seen = vec![board.get_hash()];
+    let mut current = *board;
+    while pv.len() < MAX_SEARCH_DEPTH as usize {
+        let Some(best_move) = memo_table
+            .get(current.get_hash())
+            .and_then(|cached| cached.best_move)
+        else {
+            break;
+        };
+        if !current.legal(best_move) {
+            break;
+        }
+        pv.push(best_move);
+        current = current.make_move_new(best_move);
+        if seen.contains(&current.get_hash()) {
+            break;
+        }
+        seen.push(current.get_hash());
+    }
+    pv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const POSITIONS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+    ];
+
+    /// Searches without pruning, reductions or extensions, so that the value is exactly the
+    /// minimax value at `depth` and may only depend on the table through bugs
+    fn root_value(
+        board: &Board,
+        depths: &[u16],
+        algorithm: RootAlgorithm,
+        megabytes: usize,
+    ) -> Vec<i32> {
+        let memo_table = TranspositionTable::new(megabytes);
+        let time_manager = TimeManager::unlimited();
+        let mut searcher =
+            Searcher::new(&memo_table, &time_manager, PositionHistory::new(board, 0));
+        searcher.selective = false;
+        depths
+            .iter()
+            .map(|&depth| searcher.start_search(board, depth, None, algorithm).value)
+            .collect()
+    }
+
+    fn assert_matches_plain_search(algorithm: RootAlgorithm, depths: &[u16]) {
+        for fen in POSITIONS {
+            let board = Board::from_str(fen).unwrap();
+            for &depth in depths {
+                assert_eq!(
+                    root_value(&board, &[depth], algorithm, 1),
+                    root_value(&board, &[depth], RootAlgorithm::Pvs, 0),
+                    "{} at depth {}",
+                    fen,
+                    depth
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pvs_with_table_matches_plain_search() {
+        assert_matches_plain_search(RootAlgorithm::Pvs, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn mtdf_with_table_matches_plain_search() {
+        assert_matches_plain_search(RootAlgorithm::Mtdf, &[1, 2, 3]);
+    }
+
+    /// Check and capture extensions keep the logical depth flat while the distance from the root
+    /// grows, and the subtree below such a node is cut off by whichever runs out first. Its stored
+    /// draft must never claim more than that, which the search asserts in debug builds.
+    #[test]
+    fn extended_nodes_store_their_real_draft() {
+        for fen in POSITIONS.iter().chain(&[
+            "q2k4/8/8/8/8/8/8/3K3Q w - - 0 1",
+            "r1bq1rk1/pp3ppp/2n1p3/3pP3/3P2Q1/2PB1N2/P4PPP/R3K2R w KQ - 0 1",
+        ]) {
+            let board = Board::from_str(fen).unwrap();
+            let memo_table = TranspositionTable::new(1);
+            let time_manager = TimeManager::unlimited();
+            let mut searcher =
+                Searcher::new(&memo_table, &time_manager, PositionHistory::new(&board, 0));
+            for depth in 1..=4 {
+                searcher.start_search(&board, depth, None, RootAlgorithm::Pvs);
+            }
+        }
+    }
+
+    /// Entries left over from shallower iterations must not be trusted by deeper ones
+    #[test]
+    fn iterative_deepening_reuse_matches_plain_search() {
+        let depths = [1, 2, 3];
+        for fen in POSITIONS {
+            let board = Board::from_str(fen).unwrap();
+            let plain: Vec<i32> = depths
+                .iter()
+                .flat_map(|&depth| root_value(&board, &[depth], RootAlgorithm::Pvs, 0))
+                .collect();
+            for algorithm in [RootAlgorithm::Pvs, RootAlgorithm::Mtdf] {
+                assert_eq!(root_value(&board, &depths, algorithm, 1), plain, "{}", fen);
+            }
+        }
+    }
+
+    /// A two-rook mate in 2, searched over several depths and then continued after the first move
+    /// with the same table. The entries left behind were stored two plies deeper than they are
+    /// probed in the continuation, so they only give the right distance if mates are stored
+    /// relative to their own node.
+    #[test]
+    fn cached_mate_keeps_its_distance() {
+        let board = Board::from_str("6k1/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+        let continuation = board.make_move_new(ChessMove::from_str("a2a7").unwrap());
+        for algorithm in [RootAlgorithm::Pvs, RootAlgorithm::Mtdf] {
+            let memo_table = TranspositionTable::new(1);
+            let time_manager = TimeManager::unlimited();
+            let mut searcher =
+                Searcher::new(&memo_table, &time_manager, PositionHistory::new(&board, 0));
+            searcher.selective = false;
+            for depth in [3, 4, 5] {
+                assert_eq!(
+                    searcher.start_search(&board, depth, None, algorithm).value,
+                    MATE_SCORE - 3,
+                    "depth {}",
+                    depth
+                );
+            }
+            searcher.history = PositionHistory::new(&continuation, 0);
+            for depth in [2, 3] {
+                assert_eq!(
+                    searcher
+                        .start_search(&continuation, depth, None, algorithm)
+                        .value,
+                    -(MATE_SCORE - 2),
+                    "continuation at depth {}",
+                    depth
+                );
+            }
+        }
+    }
+}
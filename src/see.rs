@@ -0,0 +1,99 @@
+use crate::constants::{PIECE_VALUES, SEE_KING_VALUE};
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard,
+    Board, ChessMove, Color, Piece, Square, ALL_PIECES, EMPTY,
+};
+use std::cmp::min;
+
+#[inline]
+fn see_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::King => SEE_KING_VALUE,
+        _ => PIECE_VALUES[piece.to_index()].value.mg,
+    }
+}
+
+/// Every piece of either colour attacking `square`, with sliders seen through `occupied` only
+#[inline]
+fn attackers_to(board: &Board, square: Square, occupied: BitBoard) -> BitBoard {
+    let pawns = board.pieces(Piece::Pawn);
+    let diagonal = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+    let straight = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    (get_pawn_attacks(
+        square,
+        Color::White,
+        pawns & board.color_combined(Color::Black),
+    ) | get_pawn_attacks(
+        square,
+        Color::Black,
+        pawns & board.color_combined(Color::White),
+    ) | (get_knight_moves(square) & board.pieces(Piece::Knight))
+        | (get_bishop_moves(square, occupied) & diagonal)
+        | (get_rook_moves(square, occupied) & straight)
+        | (get_king_moves(square) & board.pieces(Piece::King)))
+        & occupied
+}
+
+/// Static Exchange Evaluation: the material balance, from the mover's point of view, of playing
+/// `chess_move` and then letting both sides recapture on its destination with their least
+/// valuable attacker for as long as it pays. Sliders uncovered by a capture (x-rays) join in.
+pub fn see(board: &Board, chess_move: ChessMove) -> i32 {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let Some(mover) = board.piece_on(source) else {
+        return 0;
+    };
+    let mut occupied = board.combined() ^ BitBoard::from_square(source);
+    let mut gain = [0; 32];
+    if let Some(victim) = board.piece_on(dest) {
+        gain[0] = see_value(victim);
+    } else if mover == Piece::Pawn && source.get_file() != dest.get_file() {
+        // En passant removes a pawn that is not on the destination square
+        gain[0] = see_value(Piece::Pawn);
+        if let Some(en_passant_square) = board.en_passant() {
+            occupied ^= BitBoard::from_square(en_passant_square);
+        }
+    }
+    let mut on_square = mover;
+    if let Some(promotion) = chess_move.get_promotion() {
+        gain[0] += see_value(promotion) - see_value(Piece::Pawn);
+        on_square = promotion;
+    }
+    let diagonal = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+    let straight = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    let mut attackers = attackers_to(board, dest, occupied);
+    let mut side = !board.side_to_move();
+    let mut depth = 0;
+    loop {
+        let side_attackers = attackers & board.color_combined(side);
+        if side_attackers == EMPTY {
+            break;
+        }
+        let (attacker, attacker_bits) = ALL_PIECES
+            .iter()
+            .map(|&piece| (piece, side_attackers & board.pieces(piece)))
+            .find(|(_, bits)| *bits != EMPTY)
+            .unwrap();
+        if attacker == Piece::King && (attackers & board.color_combined(!side)) != EMPTY {
+            // The king may not recapture into a defended square
+            break;
+        }
+        depth += 1;
+        gain[depth] = see_value(on_square) - gain[depth - 1];
+        if depth + 1 == gain.len() {
+            break;
+        }
+        occupied ^= BitBoard::from_square(attacker_bits.to_square());
+        attackers |= (get_bishop_moves(dest, occupied) & diagonal)
+            | (get_rook_moves(dest, occupied) & straight);
+        attackers &= occupied;
+        on_square = attacker;
+        side = !side;
+    }
+    while depth > 0 {
+        // Each side may decline to continue the exchange if recapturing would lose material
+        gain[depth - 1] = min(gain[depth - 1], -gain[depth]);
+        depth -= 1;
+    }
+    gain[0]
+}
@@ -0,0 +1,172 @@
+use crate::constants::{
+    DEFAULT_MOVES_TO_GO, HARD_LIMIT_MAX_PERCENT, HARD_LIMIT_SCALE, INCREMENT_USAGE_PERCENT,
+    TIME_SAFETY_MARGIN,
+};
+use chess::Color;
+use std::cmp::min;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub struct GoParams {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub movetime: Option<u64>,
+    pub depth: Option<u16>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+fn parse_arg<T: FromStr>(args: &[&str], pos: usize) -> Option<T> {
+    args.get(pos + 1).and_then(|value| value.parse::<T>().ok())
+}
+
+impl GoParams {
+    pub fn parse(args: &[&str]) -> GoParams {
+        let mut params = GoParams::default();
+        for (pos, &arg) in args.iter().enumerate() {
+            match arg {
+                "wtime" => params.wtime = parse_arg(args, pos),
+                "btime" => params.btime = parse_arg(args, pos),
+                "winc" => params.winc = parse_arg(args, pos),
+                "binc" => params.binc = parse_arg(args, pos),
+                "movestogo" => params.movestogo = parse_arg(args, pos),
+                "movetime" => params.movetime = parse_arg(args, pos),
+                "depth" => params.depth = parse_arg(args, pos),
+                "infinite" => params.infinite = true,
+                "ponder" => params.ponder = true,
+                _ => {}
+            }
+        }
+        params
+    }
+
+    /// Whether the search is bounded by a clock rather than only by depth
+    pub fn is_timed(&self) -> bool {
+        !self.infinite && (self.movetime.is_some() || self.wtime.is_some() || self.btime.is_some())
+    }
+}
+
+/// Converts UCI clock parameters into a soft budget, after which no new iteration is started,
+/// and a hard budget, after which the running iteration is abandoned.
+///
+/// The manager is shared between the search thread and the input loop, which can stop the search
+/// or start the clock on a `ponderhit` at any time.
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+    infinite: bool,
+    stop: AtomicBool,
+    pondering: AtomicBool,
+    clock_start_millis: AtomicU64,
+}
+
+impl TimeManager {
+    pub fn unlimited() -> TimeManager {
+        TimeManager {
+            start: Instant::now(),
+            soft_limit: None,
+            hard_limit: None,
+            infinite: false,
+            stop: AtomicBool::new(false),
+            pondering: AtomicBool::new(false),
+            clock_start_millis: AtomicU64::new(0),
+        }
+    }
+
+    pub fn new(params: &GoParams, side: Color, move_overhead: u64) -> TimeManager {
+        let mut manager = TimeManager::unlimited();
+        manager.pondering = AtomicBool::new(params.ponder);
+        if params.infinite {
+            manager.infinite = true;
+            return manager;
+        }
+        if let Some(movetime) = params.movetime {
+            let budget = Duration::from_millis(movetime.saturating_sub(move_overhead).max(1));
+            manager.soft_limit = Some(budget);
+            manager.hard_limit = Some(budget);
+            return manager;
+        }
+        let (time, increment) = match side {
+            Color::White => (params.wtime, params.winc),
+            Color::Black => (params.btime, params.binc),
+        };
+        if let Some(time) = time {
+            let available = time
+                .saturating_sub(move_overhead + TIME_SAFETY_MARGIN)
+                .max(1);
+            let moves_to_go = params.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            let increment = increment.unwrap_or(0) * INCREMENT_USAGE_PERCENT / 100;
+            let soft = available / moves_to_go + increment;
+            // Even the last move before a time control must leave time on the clock, since the
+            // search only notices the hard limit between nodes
+            let hard = min(
+                soft * HARD_LIMIT_SCALE,
+                available * HARD_LIMIT_MAX_PERCENT / 100,
+            );
+            let soft = min(soft, hard);
+            manager.soft_limit = Some(Duration::from_millis(soft.max(1)));
+            manager.hard_limit = Some(Duration::from_millis(hard.max(1)));
+        }
+        manager
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Time spent since the clock started, which is delayed until `ponderhit` when pondering
+    fn clock_elapsed(&self) -> Duration {
+        self.elapsed().saturating_sub(Duration::from_millis(
+            self.clock_start_millis.load(Ordering::Relaxed),
+        ))
+    }
+
+    fn clock_running(&self) -> bool {
+        !self.pondering.load(Ordering::Relaxed)
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn ponderhit(&self) {
+        self.clock_start_millis
+            .store(self.elapsed().as_millis() as u64, Ordering::Relaxed);
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn soft_expired(&self) -> bool {
+        self.is_stopped()
+            || (self.clock_running()
+                && self
+                    .soft_limit
+                    .is_some_and(|limit| self.clock_elapsed() >= limit))
+    }
+
+    pub fn hard_expired(&self) -> bool {
+        self.is_stopped()
+            || (self.clock_running()
+                && self
+                    .hard_limit
+                    .is_some_and(|limit| self.clock_elapsed() >= limit))
+    }
+
+    /// UCI forbids reporting a best move from an infinite or pondering search before being told to
+    /// stop, so a search that finishes early has to wait here
+    pub fn wait_for_release(&self) {
+        while !self.is_stopped() && (self.infinite || !self.clock_running()) {
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
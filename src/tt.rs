@@ -0,0 +1,197 @@
+use crate::search::{NodeType, SearchResult};
+use chess::{ChessMove, ALL_PIECES, ALL_SQUARES};
+use std::cmp::max;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+/// Entries per bucket, so that a bucket of 16-byte entries fills one 64-byte cache line
+const BUCKET_SIZE: usize = 4;
+const GENERATION_SHIFT: u32 = 58;
+const GENERATION_MASK: u8 = 0b11111;
+const VALID_BIT: u64 = 1 << 63;
+
+/// A single slot. The key is stored XORed with the data, so that a slot torn by two threads
+/// writing at once fails verification on the next probe instead of returning another position's
+/// data.
+#[derive(Default)]
+struct Entry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Entry {
+    #[inline]
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        (self.key.load(Ordering::Relaxed) ^ data, data)
+    }
+
+    #[inline]
+    fn store(&self, hash: u64, data: u64) {
+        self.key.store(hash ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket([Entry; BUCKET_SIZE]);
+
+/// Transposition table shared by every search thread without locking. A position may be stored
+/// in any entry of the bucket its hash selects, and entries left over from earlier searches are
+/// the first to be replaced.
+pub struct TranspositionTable {
+    buckets: Box<[Bucket]>,
+    mask: usize,
+    generation: AtomicU8,
+}
+
+/// Moves are packed as 6 bits of source, 6 bits of destination and 3 bits of promotion, where 0
+/// means none. No legal move encodes to 0, which is kept to mean "no best move".
+#[inline]
+fn pack_move(chess_move: Option<ChessMove>) -> u64 {
+    chess_move.map_or(0, |chess_move| {
+        chess_move.get_source().to_index() as u64
+            | (chess_move.get_dest().to_index() as u64) << 6
+            | chess_move
+                .get_promotion()
+                .map_or(0, |piece| piece.to_index() as u64 + 1)
+                << 12
+    })
+}
+
+#[inline]
+fn unpack_move(bits: u64) -> Option<ChessMove> {
+    if bits == 0 {
+        return None;
+    }
+    let promotion = (bits >> 12) & 0b111;
+    Some(ChessMove::new(
+        ALL_SQUARES[(bits & 0x3F) as usize],
+        ALL_SQUARES[((bits >> 6) & 0x3F) as usize],
+        (promotion != 0).then(|| ALL_PIECES[promotion as usize - 1]),
+    ))
+}
+
+/// Packs a result into 64 bits: the value in the low 32 bits, then the move, depth, node type and
+/// the generation of the search that stored it
+#[inline]
+fn pack(result: &SearchResult, generation: u8) -> u64 {
+    let node_type = match result.node_type {
+        NodeType::PV => 0,
+        NodeType::Cut => 1,
+        NodeType::All => 2,
+    };
+    result.value as u32 as u64
+        | pack_move(result.best_move) << 32
+        | (result.depth.min(u8::MAX as u16) as u64) << 48
+        | node_type << 56
+        | (generation as u64) << GENERATION_SHIFT
+        | VALID_BIT
+}
+
+#[inline]
+fn unpack(data: u64) -> SearchResult {
+    let mut result = SearchResult::new(
+        data as u32 as i32,
+        unpack_move((data >> 32) & 0xFFFF),
+        ((data >> 48) & 0xFF) as u16,
+    );
+    result.node_type = match (data >> 56) & 0b11 {
+        0 => NodeType::PV,
+        1 => NodeType::Cut,
+        _ => NodeType::All,
+    };
+    result
+}
+
+#[inline]
+fn generation_of(data: u64) -> u8 {
+    (data >> GENERATION_SHIFT) as u8 & GENERATION_MASK
+}
+
+impl TranspositionTable {
+    /// Creates the largest table with a power of two number of buckets that fits in `megabytes`. A
+    /// table of 0 megabytes stores nothing.
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let buckets = match megabytes {
+            0 => 0,
+            _ => 1 << max(megabytes * 1024 * 1024 / size_of::<Bucket>(), 1).ilog2(),
+        };
+        TranspositionTable {
+            buckets: std::iter::repeat_with(Bucket::default)
+                .take(buckets)
+                .collect(),
+            mask: buckets.saturating_sub(1),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    #[inline]
+    fn bucket(&self, hash: u64) -> Option<&Bucket> {
+        self.buckets.get(hash as usize & self.mask)
+    }
+
+    #[inline]
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Ages every stored entry by one search, making them the first candidates for replacement
+    pub fn new_search(&self) {
+        self.generation
+            .store((self.generation() + 1) & GENERATION_MASK, Ordering::Relaxed);
+    }
+
+    /// Empties every entry, so that nothing from previous searches or games is reused
+    pub fn clear(&self) {
+        for entry in self.buckets.iter().flat_map(|bucket| &bucket.0) {
+            entry.store(0, 0);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, hash: u64) -> Option<SearchResult> {
+        self.bucket(hash)?
+            .0
+            .iter()
+            .map(Entry::load)
+            .find(|&(key, data)| data & VALID_BIT != 0 && key == hash)
+            .map(|(_, data)| unpack(data))
+    }
+
+    /// Stores `result`, overwriting an existing entry for the same position only if `replace`
+    /// accepts it. Otherwise an empty entry is filled, then one left over from an earlier search,
+    /// and finally the first entry of the current search that `replace` accepts.
+    pub fn replace_if<F: Fn(SearchResult) -> bool>(
+        &self,
+        hash: u64,
+        result: SearchResult,
+        replace: F,
+    ) {
+        let generation = self.generation();
+        let Some(Bucket(entries)) = self.bucket(hash) else {
+            return;
+        };
+        let loaded = entries.each_ref().map(Entry::load);
+        let target = if let Some(same) = loaded
+            .iter()
+            .position(|&(key, data)| data & VALID_BIT != 0 && key == hash)
+        {
+            Some(same).filter(|&same| replace(unpack(loaded[same].1)))
+        } else {
+            loaded
+                .iter()
+                .position(|&(_, data)| data & VALID_BIT == 0)
+                .or_else(|| {
+                    loaded
+                        .iter()
+                        .position(|&(_, data)| generation_of(data) != generation)
+                })
+                .or_else(|| loaded.iter().position(|&(_, data)| replace(unpack(data))))
+        };
+        if let Some(target) = target {
+            entries[target].store(hash, pack(&result, generation));
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use crate::constants::{MATERIAL_SCALE, MATE_BOUND, MATE_SCORE, MAX_SEARCH_DEPTH};
+use crate::history::PositionHistory;
+use crate::perft::{divide, perft};
+use crate::pst::{load_piece_square_tables, reset_piece_square_tables};
+use crate::search::{lazy_smp, RootAlgorithm, SearchResult};
+use crate::timeman::{GoParams, TimeManager};
+use crate::tt::TranspositionTable;
+use chess::{Board, ChessMove};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread::{spawn, JoinHandle};
+use std::time::Duration;
+
+pub const ENGINE_NAME: &str = "BetaFish";
+pub const ENGINE_AUTHOR: &str = "Gerald Lee";
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+pub struct EngineOptions {
+    pub depth: u16,
+    pub hash: usize,
+    pub move_overhead: u64,
+    pub threads: usize,
+    pub algorithm: RootAlgorithm,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        EngineOptions {
+            depth: 7,
+            hash: 16,
+            move_overhead: 0,
+            threads: 1,
+            algorithm: RootAlgorithm::Mtdf,
+        }
+    }
+}
+
+pub struct EngineContext {
+    pub options: EngineOptions,
+    pub debug: bool,
+    pub position: Board,
+    pub history: PositionHistory,
+    pub memo_table: Arc<TranspositionTable>,
+    pub run_loop: bool,
+    search_thread: Option<JoinHandle<()>>,
+    time_manager: Option<Arc<TimeManager>>,
+}
+
+impl EngineContext {
+    pub fn new() -> EngineContext {
+        EngineContext {
+            options: EngineOptions::default(),
+            debug: false,
+            position: Board::default(),
+            history: PositionHistory::new(&Board::default(), 0),
+            memo_table: Arc::new(TranspositionTable::new(EngineOptions::default().hash)),
+            run_loop: true,
+            search_thread: None,
+            time_manager: None,
+        }
+    }
+
+    /// Signals any running search to stop and waits for it to report its best move
+    pub fn stop_search(&mut self) {
+        if let Some(time_manager) = self.time_manager.take() {
+            time_manager.stop();
+        }
+        self.wait_for_search();
+    }
+
+    /// Blocks until any running search has finished on its own
+    pub fn wait_for_search(&mut self) {
+        if let Some(search_thread) = self.search_thread.take() {
+            search_thread.join().unwrap();
+        }
+        self.time_manager = None;
+    }
+}
+
+fn send_info_string(message: &str) {
+    println!("info string {}", message);
+}
+
+/// Formats a search value as a UCI score, using `mate N` (negative when being mated) for forced
+/// mates
+fn format_score(value: i32) -> String {
+    if value.abs() >= MATE_BOUND {
+        let plies = MATE_SCORE - value.abs();
+        let moves = (plies + 1) / 2;
+        format!("mate {}", if value > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", value / MATERIAL_SCALE)
+    }
+}
+
+/// Reports a completed iteration
+pub fn send_search_info(
+    depth: u16,
+    seldepth: u16,
+    result: &SearchResult,
+    pv: &[ChessMove],
+    nodes: u64,
+    elapsed: Duration,
+) {
+    let millis = elapsed.as_millis() as u64;
+    let nps = nodes * 1000 / millis.max(1);
+    let pv = pv
+        .iter()
+        .map(ChessMove::to_string)
+        .collect::<Vec<String>>()
+        .join(" ");
+    println!(
+        "info depth {} seldepth {} score {} nodes {} nps {} time {} pv {}",
+        depth,
+        seldepth,
+        format_score(result.value),
+        nodes,
+        nps,
+        millis,
+        pv
+    );
+}
+
+fn handle_uci(_context: &mut EngineContext, _args: &[&str]) {
+    println!("id name {}", ENGINE_NAME);
+    println!("id author {}", ENGINE_AUTHOR);
+    println!("option name Depth type spin default 7 min 1 max 32");
+    println!("option name Hash type spin default 16 min 1 max 1024");
+    println!("option name Clear Hash type button");
+    println!("option name Move Overhead type spin default 0 min 0 max 5000");
+    println!("option name Threads type spin default 1 min 1 max 128");
+    println!("option name Piece Square Tables type string default <empty>");
+    println!("option name Search Algorithm type combo default MTD(f) var MTD(f) var PVS");
+    println!("uciok");
+}
+
+fn handle_debug(context: &mut EngineContext, args: &[&str]) {
+    match args.first() {
+        Some(&"on") => context.debug = true,
+        Some(&"off") => context.debug = false,
+        _ => send_info_string("Command debug expected one of 'on, off' as argument 0"),
+    }
+}
+
+fn handle_isready(_context: &mut EngineContext, _args: &[&str]) {
+    println!("readyok");
+}
+
+fn parse_option<T: FromStr>(name: &str, value: Option<&str>, target: &mut T) {
+    match value.map(str::parse::<T>) {
+        Some(Ok(parsed)) => *target = parsed,
+        _ => send_info_string(&format!("Invalid value for option {}", name)),
+    }
+}
+
+fn handle_setoption(context: &mut EngineContext, args: &[&str]) {
+    if args.first() != Some(&"name") {
+        send_info_string("Command setoption expected 'name' as argument 0");
+        return;
+    }
+    let value_pos = args.iter().position(|&arg| arg == "value");
+    let name = args[1..value_pos.unwrap_or(args.len())].join(" ");
+    let value = value_pos.map(|pos| args[pos + 1..].join(" "));
+    let value = value.as_deref();
+    context.stop_search();
+    let options = &mut context.options;
+    match name.to_lowercase().as_str() {
+        "depth" => {
+            parse_option(&name, value, &mut options.depth);
+            options.depth = options.depth.clamp(1, 32);
+        }
+        "hash" => {
+            let previous = options.hash;
+            parse_option(&name, value, &mut options.hash);
+            options.hash = options.hash.clamp(1, 1024);
+            // An invalid or unchanged size keeps the table and everything in it
+            if options.hash != previous {
+                // Free the old table before allocating the new one
+                context.memo_table = Arc::new(TranspositionTable::new(1));
+                context.memo_table = Arc::new(TranspositionTable::new(options.hash));
+            }
+        }
+        "clear hash" => context.memo_table.clear(),
+        "move overhead" => parse_option(&name, value, &mut options.move_overhead),
+        "threads" => {
+            parse_option(&name, value, &mut options.threads);
+            options.threads = options.threads.clamp(1, 128);
+        }
+        "piece square tables" => match value {
+            None | Some("<empty>") => reset_piece_square_tables(),
+            Some(path) => {
+                if let Err(error) = load_piece_square_tables(path) {
+                    send_info_string(&format!("Could not load {} | {}", path, error));
+                }
+            }
+        },
+        "search algorithm" => match value.map(str::to_lowercase).as_deref() {
+            Some("mtd(f)") => options.algorithm = RootAlgorithm::Mtdf,
+            Some("pvs") => options.algorithm = RootAlgorithm::Pvs,
+            _ => send_info_string(&format!("Invalid value for option {}", name)),
+        },
+        _ => send_info_string(&format!("Unknown option {}", name)),
+    }
+}
+
+fn handle_ucinewgame(context: &mut EngineContext, _args: &[&str]) {
+    context.stop_search();
+    context.memo_table.clear();
+    context.position = Board::default();
+    context.history = PositionHistory::new(&context.position, 0);
+}
+
+fn handle_position(context: &mut EngineContext, args: &[&str]) {
+    context.stop_search();
+    let moves_pos = args.iter().position(|&arg| arg == "moves");
+    let setup = &args[..moves_pos.unwrap_or(args.len())];
+    let board = match setup.first() {
+        Some(&"startpos") => Board::default(),
+        Some(&"fen") => match Board::from_str(&setup[1..].join(" ")) {
+            Ok(board) => board,
+            Err(error) => {
+                send_info_string(&format!("Invalid fen {} | {}", setup[1..].join(" "), error));
+                return;
+            }
+        },
+        _ => {
+            send_info_string("Command position expected one of 'fen, startpos' as argument 0");
+            return;
+        }
+    };
+    let halfmove_clock = match setup.first() {
+        Some(&"fen") => setup
+            .get(5)
+            .and_then(|clock| clock.parse().ok())
+            .unwrap_or(0),
+        _ => 0,
+    };
+    context.position = board;
+    context.history = PositionHistory::new(&board, halfmove_clock);
+    if let Some(pos) = moves_pos {
+        for move_text in &args[pos + 1..] {
+            match ChessMove::from_str(move_text) {
+                Ok(chess_move) if context.position.legal(chess_move) => {
+                    let new_board = context.position.make_move_new(chess_move);
+                    context
+                        .history
+                        .push(&context.position, chess_move, &new_board);
+                    context.position = new_board;
+                }
+                _ => {
+                    send_info_string(&format!("Illegal move {}", move_text));
+                    return;
+                }
+            }
+        }
+    }
+    if context.debug {
+        send_info_string(&format!("Position set to {}", context.position));
+    }
+}
+
+fn handle_go(context: &mut EngineContext, args: &[&str]) {
+    context.stop_search();
+    let params = GoParams::parse(args);
+    let time_manager = Arc::new(TimeManager::new(
+        &params,
+        context.position.side_to_move(),
+        context.options.move_overhead,
+    ));
+    // Deeper searches would reach plies where mates no longer fit above `MATE_BOUND`
+    let depth = params
+        .depth
+        .map(|depth| depth.clamp(1, MAX_SEARCH_DEPTH))
+        .unwrap_or(if params.is_timed() || params.infinite {
+            MAX_SEARCH_DEPTH
+        } else {
+            context.options.depth
+        });
+    let board = context.position;
+    let algorithm = context.options.algorithm;
+    let threads = context.options.threads.max(1);
+    if context.debug {
+        send_info_string(&format!(
+            "Searching to depth {} | threads {} | hash {} MB",
+            depth, threads, context.options.hash
+        ));
+    }
+    let history = context.history.clone();
+    let memo_table = Arc::clone(&context.memo_table);
+    let search_time_manager = Arc::clone(&time_manager);
+    context.time_manager = Some(time_manager);
+    context.search_thread = Some(spawn(move || {
+        let result = lazy_smp(
+            &board,
+            depth,
+            algorithm,
+            threads,
+            &memo_table,
+            &search_time_manager,
+            history,
+        );
+        search_time_manager.wait_for_release();
+        println!(
+            "bestmove {}",
+            result
+                .best_move
+                .map_or("0000".to_string(), |best_move| best_move.to_string())
+        );
+    }));
+}
+
+fn handle_stop(context: &mut EngineContext, _args: &[&str]) {
+    context.stop_search();
+}
+
+fn handle_ponderhit(context: &mut EngineContext, _args: &[&str]) {
+    if let Some(time_manager) = &context.time_manager {
+        time_manager.ponderhit();
+    }
+}
+
+fn handle_quit(context: &mut EngineContext, _args: &[&str]) {
+    context.stop_search();
+    context.run_loop = false;
+}
+
+/// Reads the depth argument of `perft` and `divide`, reporting a missing or malformed one. Depth
+/// 0 is rejected, since it has no moves to split by and the two commands would disagree on it.
+fn parse_perft_depth(command: &str, args: &[&str]) -> Option<u16> {
+    let depth = args
+        .first()
+        .and_then(|depth| depth.parse::<u16>().ok())
+        .filter(|&depth| depth > 0);
+    if depth.is_none() {
+        send_info_string(&format!(
+            "Command {} expected a positive depth as argument 0",
+            command
+        ));
+    }
+    depth
+}
+
+fn handle_perft(context: &mut EngineContext, args: &[&str]) {
+    let Some(depth) = parse_perft_depth("perft", args) else {
+        return;
+    };
+    context.stop_search();
+    println!("Nodes searched: {}", perft(&context.position, depth));
+}
+
+fn handle_divide(context: &mut EngineContext, args: &[&str]) {
+    let Some(depth) = parse_perft_depth("divide", args) else {
+        return;
+    };
+    context.stop_search();
+    let split = divide(&context.position, depth);
+    for (chess_move, nodes) in &split {
+        println!("{}: {}", chess_move, nodes);
+    }
+    println!(
+        "Nodes searched: {}",
+        split.iter().map(|(_, nodes)| nodes).sum::<u64>()
+    );
+}
+
+/// Dispatches a single line of UCI input, returning false if the command is not a UCI command
+pub fn handle_command(context: &mut EngineContext, line: &str) -> bool {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let Some((&command, args)) = tokens.split_first() else {
+        return true;
+    };
+    let handler: fn(&mut EngineContext, &[&str]) = match command {
+        "uci" => handle_uci,
+        "debug" => handle_debug,
+        "isready" => handle_isready,
+        "setoption" => handle_setoption,
+        "ucinewgame" => handle_ucinewgame,
+        "position" => handle_position,
+        "go" => handle_go,
+        "stop" => handle_stop,
+        "ponderhit" => handle_ponderhit,
+        "quit" => handle_quit,
+        "perft" => handle_perft,
+        "divide" => handle_divide,
+        _ => return false,
+    };
+    handler(context, args);
+    true
+}